@@ -0,0 +1,89 @@
+//! Remote signer holding validator keys outside the node process.
+//!
+//! Usage: `jamzig-signer <keystore> <socket>`
+//!
+//! The keystore password is taken from `JAMZIG_SIGNER_PASSWORD`, or read as
+//! the first line of stdin if that is unset. The socket is created with mode
+//! 0600, so only the user running the signer (and the node, if it runs as the
+//! same user) can request signatures. See `jamzig_crypto::remote_signer` for
+//! the protocol.
+
+use std::fs;
+use std::io::{self, BufRead};
+use std::os::unix::fs::FileTypeExt;
+use std::os::unix::net::UnixListener;
+use std::path::Path;
+use std::process::ExitCode;
+use std::sync::Arc;
+
+use jamzig_crypto::keystore::{KeyType, Keystore};
+use jamzig_crypto::remote_signer::serve;
+use zeroize::Zeroizing;
+
+const PASSWORD_ENV: &str = "JAMZIG_SIGNER_PASSWORD";
+
+fn read_password() -> io::Result<Zeroizing<String>> {
+  if let Ok(password) = std::env::var(PASSWORD_ENV) {
+    return Ok(Zeroizing::new(password));
+  }
+  let mut line = Zeroizing::new(String::new());
+  io::stdin().lock().read_line(&mut line)?;
+  let len = line.trim_end_matches(['\r', '\n']).len();
+  line.truncate(len);
+  Ok(line)
+}
+
+fn bind(socket: &Path) -> io::Result<UnixListener> {
+  // Replace a socket left behind by a previous run, but never other files
+  if let Ok(metadata) = fs::symlink_metadata(socket) {
+    if metadata.file_type().is_socket() {
+      fs::remove_file(socket)?;
+    }
+  }
+  // Create the socket with mode 0600 straight away; changing the mode after
+  // bind would leave a window in which any local user could connect. No
+  // other threads run yet, so the process-wide umask is safe to swap.
+  let umask = unsafe { libc::umask(0o177) };
+  let listener = UnixListener::bind(socket);
+  unsafe { libc::umask(umask) };
+  listener
+}
+
+fn run(keystore: &Path, socket: &Path) -> Result<(), String> {
+  let password = read_password().map_err(|e| format!("password: {e}"))?;
+  let keystore = Keystore::open(keystore, password.as_bytes())
+    .map_err(|e| format!("{}: {e}", keystore.display()))?;
+
+  for (name, key_type) in [
+    ("ed25519", KeyType::Ed25519),
+    ("bandersnatch", KeyType::Bandersnatch),
+  ] {
+    for public in keystore.public_keys(key_type) {
+      eprintln!("jamzig-signer: {name} {}", hex::encode(public));
+    }
+  }
+
+  let listener =
+    bind(socket).map_err(|e| format!("{}: {e}", socket.display()))?;
+  eprintln!("jamzig-signer: listening on {}", socket.display());
+  serve(listener, Arc::new(keystore), |e| {
+    eprintln!("jamzig-signer: connection closed: {e}");
+  })
+  .map_err(|e| e.to_string())
+}
+
+fn main() -> ExitCode {
+  let args: Vec<String> = std::env::args().collect();
+  let [_, keystore, socket] = args.as_slice() else {
+    eprintln!("usage: jamzig-signer <keystore> <socket>");
+    return ExitCode::from(2);
+  };
+
+  match run(Path::new(keystore), Path::new(socket)) {
+    Ok(()) => ExitCode::SUCCESS,
+    Err(e) => {
+      eprintln!("jamzig-signer: {e}");
+      ExitCode::FAILURE
+    }
+  }
+}
@@ -1,15 +1,124 @@
-//! ZIP-215 compliant Ed25519 signature verification
+//! ZIP-215 compliant Ed25519 signing and signature verification
 //!
 //! This module provides FFI bindings for ed25519-consensus, ensuring consistent
 //! signature validation across all JAM implementations per ZIP-215 specification.
 
-use ed25519_consensus::{Signature, VerificationKey};
+use curve25519_dalek_ng::{
+  edwards::{CompressedEdwardsY, EdwardsPoint},
+  scalar::Scalar,
+};
+use ed25519_consensus::{
+  batch, Signature, SigningKey, VerificationKey, VerificationKeyBytes,
+};
 use libc::c_int;
+use sha2::{Digest, Sha512};
 use std::convert::TryFrom;
 
+const SEED_LENGTH: usize = 32;
 const PUBLIC_KEY_LENGTH: usize = 32;
 const SIGNATURE_LENGTH: usize = 64;
 
+/// Bit set by `ed25519_verify_modes` when the signature is valid under ZIP-215.
+pub const ED25519_ZIP215_VALID: c_int = 1;
+/// Bit set by `ed25519_verify_modes` when the signature is valid under strict
+/// RFC 8032 rules.
+pub const ED25519_RFC8032_VALID: c_int = 1 << 1;
+/// Bit set by `ed25519_verify_modes` when the signature is valid under
+/// libsodium's rules.
+pub const ED25519_LIBSODIUM_VALID: c_int = 1 << 2;
+
+/// Derive an Ed25519 keypair from a 32-byte seed.
+///
+/// The seed is the secret key: it is what `ed25519_sign` takes, and the same
+/// seed always yields the same public key.
+///
+/// # Arguments
+/// * `seed` - 32-byte secret seed
+/// * `public_key_out` - Buffer receiving the 32-byte public key
+///
+/// # Returns
+/// * `0` - Public key written
+/// * `-1` - Inputs are malformed
+///
+/// # Safety
+/// Caller must ensure all pointers are valid and point to appropriately sized buffers.
+#[no_mangle]
+pub unsafe extern "C" fn ed25519_keypair_from_seed(
+  seed: *const u8,
+  public_key_out: *mut u8,
+) -> c_int {
+  if seed.is_null() || public_key_out.is_null() {
+    return -1;
+  }
+
+  let seed_bytes: [u8; SEED_LENGTH] =
+    match std::slice::from_raw_parts(seed, SEED_LENGTH).try_into() {
+      Ok(bytes) => bytes,
+      Err(_) => return -1,
+    };
+
+  let vk = SigningKey::from(seed_bytes).verification_key();
+  std::ptr::copy_nonoverlapping(
+    vk.as_bytes().as_ptr(),
+    public_key_out,
+    PUBLIC_KEY_LENGTH,
+  );
+
+  0
+}
+
+/// Sign a message with the Ed25519 key derived from a 32-byte seed.
+///
+/// Signatures are deterministic, so signing the same message twice with the
+/// same seed yields identical bytes.
+///
+/// # Arguments
+/// * `seed` - 32-byte secret seed, as passed to `ed25519_keypair_from_seed`
+/// * `message` - Message bytes to sign
+/// * `message_len` - Length of message in bytes
+/// * `signature_out` - Buffer receiving the 64-byte signature
+///
+/// # Returns
+/// * `0` - Signature written
+/// * `-1` - Inputs are malformed
+///
+/// # Safety
+/// Caller must ensure all pointers are valid and point to appropriately sized buffers.
+#[no_mangle]
+pub unsafe extern "C" fn ed25519_sign(
+  seed: *const u8,
+  message: *const u8,
+  message_len: usize,
+  signature_out: *mut u8,
+) -> c_int {
+  if seed.is_null() || signature_out.is_null() {
+    return -1;
+  }
+
+  // Allow null message only if length is 0
+  if message.is_null() && message_len > 0 {
+    return -1;
+  }
+
+  let seed_bytes: [u8; SEED_LENGTH] =
+    match std::slice::from_raw_parts(seed, SEED_LENGTH).try_into() {
+      Ok(bytes) => bytes,
+      Err(_) => return -1,
+    };
+
+  let msg = if message_len == 0 {
+    &[]
+  } else {
+    std::slice::from_raw_parts(message, message_len)
+  };
+
+  let sig: [u8; SIGNATURE_LENGTH] =
+    SigningKey::from(seed_bytes).sign(msg).into();
+  std::ptr::copy_nonoverlapping(sig.as_ptr(), signature_out, SIGNATURE_LENGTH);
+
+  0
+}
+
 /// Verify an Ed25519 signature using ZIP-215 compliant validation rules.
 ///
 /// ZIP-215 ensures deterministic validation that is consistent with batch
@@ -75,10 +184,396 @@ pub unsafe extern "C" fn ed25519_verify(
   }
 }
 
+/// JAM signing contexts for Ed25519 signatures (Gray Paper appendix I.4.5).
+///
+/// Every Ed25519 signature in JAM covers one of these context strings followed
+/// by a payload. Contexts signed with Bandersnatch keys (`jam_audit`,
+/// `jam_entropy`, `jam_fallback_seal`, `jam_ticket_seal`) and BLS keys
+/// (`jam_beefy`) are deliberately not part of this enum.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(u8)]
+pub enum SigningContext {
+  /// `jam_available`: availability assurances (X_A)
+  Available = 0,
+  /// `jam_guarantee`: work-report guarantees and culprits (X_G)
+  Guarantee = 1,
+  /// `jam_announce`: audit announcements (X_I)
+  Announce = 2,
+  /// `jam_valid`: positive judgements and faults (X_⊤)
+  Valid = 3,
+  /// `jam_invalid`: negative judgements and faults (X_⊥)
+  Invalid = 4,
+}
+
+impl SigningContext {
+  /// Context string prepended to the payload.
+  pub fn prefix(self) -> &'static [u8] {
+    match self {
+      SigningContext::Available => b"jam_available",
+      SigningContext::Guarantee => b"jam_guarantee",
+      SigningContext::Announce => b"jam_announce",
+      SigningContext::Valid => b"jam_valid",
+      SigningContext::Invalid => b"jam_invalid",
+    }
+  }
+
+  /// Builds the signed message: context string followed by the payload.
+  pub fn message(self, payload: &[u8]) -> Vec<u8> {
+    [self.prefix(), payload].concat()
+  }
+}
+
+impl TryFrom<u8> for SigningContext {
+  type Error = ();
+
+  fn try_from(value: u8) -> Result<Self, Self::Error> {
+    match value {
+      0 => Ok(SigningContext::Available),
+      1 => Ok(SigningContext::Guarantee),
+      2 => Ok(SigningContext::Announce),
+      3 => Ok(SigningContext::Valid),
+      4 => Ok(SigningContext::Invalid),
+      _ => Err(()),
+    }
+  }
+}
+
+/// Sign `context ++ payload` with the Ed25519 key derived from a 32-byte seed.
+///
+/// # Arguments
+/// * `seed` - 32-byte secret seed, as passed to `ed25519_keypair_from_seed`
+/// * `context` - `SigningContext` discriminant
+/// * `payload` - Payload bytes following the context string
+/// * `payload_len` - Length of payload in bytes
+/// * `signature_out` - Buffer receiving the 64-byte signature
+///
+/// # Returns
+/// * `0` - Signature written
+/// * `-1` - Unknown context or malformed inputs
+///
+/// # Safety
+/// Caller must ensure all pointers are valid and point to appropriately sized buffers.
+#[no_mangle]
+pub unsafe extern "C" fn ed25519_sign_with_context(
+  seed: *const u8,
+  context: u8,
+  payload: *const u8,
+  payload_len: usize,
+  signature_out: *mut u8,
+) -> c_int {
+  let Ok(context) = SigningContext::try_from(context) else {
+    return -1;
+  };
+
+  // Allow null payload only if length is 0
+  if payload.is_null() && payload_len > 0 {
+    return -1;
+  }
+
+  let payload = if payload_len == 0 {
+    &[]
+  } else {
+    std::slice::from_raw_parts(payload, payload_len)
+  };
+
+  let message = context.message(payload);
+  ed25519_sign(seed, message.as_ptr(), message.len(), signature_out)
+}
+
+/// Verify a signature over `context ++ payload` using ZIP-215 rules.
+///
+/// # Arguments
+/// * `public_key` - 32-byte Ed25519 public key
+/// * `signature` - 64-byte Ed25519 signature
+/// * `context` - `SigningContext` discriminant
+/// * `payload` - Payload bytes following the context string
+/// * `payload_len` - Length of payload in bytes
+///
+/// # Returns
+/// * `0` - Signature is valid
+/// * `-1` - Signature is invalid, context is unknown or inputs are malformed
+///
+/// # Safety
+/// Caller must ensure all pointers are valid and point to appropriately sized buffers.
+#[no_mangle]
+pub unsafe extern "C" fn ed25519_verify_with_context(
+  public_key: *const u8,
+  signature: *const u8,
+  context: u8,
+  payload: *const u8,
+  payload_len: usize,
+) -> c_int {
+  let Ok(context) = SigningContext::try_from(context) else {
+    return -1;
+  };
+
+  // Allow null payload only if length is 0
+  if payload.is_null() && payload_len > 0 {
+    return -1;
+  }
+
+  let payload = if payload_len == 0 {
+    &[]
+  } else {
+    std::slice::from_raw_parts(payload, payload_len)
+  };
+
+  let message = context.message(payload);
+  ed25519_verify(public_key, signature, message.as_ptr(), message.len())
+}
+
+/// Outcome of checking one signature under each supported rule set.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct VerifyModes {
+  /// ZIP-215: any decodable `A` and `R` (canonical or not, any order), `S`
+  /// reduced, cofactored equation `[8][S]B = [8]R + [8][k]A`. These are the
+  /// rules `ed25519_verify` applies.
+  pub zip215: bool,
+  /// Strict RFC 8032 (section 5.1.7): `A` and `R` canonically encoded, `S`
+  /// reduced, cofactorless equation `[S]B = R + [k]A`.
+  pub rfc8032: bool,
+  /// libsodium: strict RFC 8032 plus rejection of small-order `A` and `R`.
+  pub libsodium: bool,
+}
+
+/// Verify a signature under ZIP-215, strict RFC 8032 and libsodium rules.
+///
+/// Meant for conformance investigations: a signature accepted by ZIP-215 but
+/// rejected by the others typically has a non-canonical or small-order
+/// component.
+pub fn verify_modes(
+  public_key: &[u8; PUBLIC_KEY_LENGTH],
+  signature: &[u8; SIGNATURE_LENGTH],
+  message: &[u8],
+) -> VerifyModes {
+  let zip215 = VerificationKey::try_from(*public_key)
+    .and_then(|vk| vk.verify(&Signature::from(*signature), message))
+    .is_ok();
+
+  let r_bytes: [u8; 32] = signature[..32].try_into().expect("R is 32 bytes");
+  let s_bytes: [u8; 32] = signature[32..].try_into().expect("S is 32 bytes");
+
+  let decode_canonical = |bytes: &[u8; 32]| -> Option<EdwardsPoint> {
+    let point = CompressedEdwardsY(*bytes).decompress()?;
+    (point.compress().as_bytes() == bytes).then_some(point)
+  };
+
+  let strict = (|| {
+    let a = decode_canonical(public_key)?;
+    let r = decode_canonical(&r_bytes)?;
+    let s = Scalar::from_canonical_bytes(s_bytes)?;
+    let k = Scalar::from_hash(
+      Sha512::default()
+        .chain(r_bytes)
+        .chain(public_key)
+        .chain(message),
+    );
+    let valid =
+      EdwardsPoint::vartime_double_scalar_mul_basepoint(&k, &-a, &s) == r;
+    Some((valid, a.is_small_order() || r.is_small_order()))
+  })();
+
+  let (rfc8032, small_order) = strict.unwrap_or((false, false));
+
+  VerifyModes {
+    zip215,
+    rfc8032,
+    libsodium: rfc8032 && !small_order,
+  }
+}
+
+/// Verify an Ed25519 signature under ZIP-215, strict RFC 8032 and libsodium
+/// rules at once, for differential testing.
+///
+/// # Arguments
+/// * `public_key` - 32-byte Ed25519 public key
+/// * `signature` - 64-byte Ed25519 signature
+/// * `message` - Message bytes that were signed
+/// * `message_len` - Length of message in bytes
+///
+/// # Returns
+/// * `>= 0` - Bitmask of `ED25519_ZIP215_VALID`, `ED25519_RFC8032_VALID` and
+///   `ED25519_LIBSODIUM_VALID` for the rule sets that accept the signature
+/// * `-1` - Inputs are malformed
+///
+/// # Safety
+/// Caller must ensure all pointers are valid and point to appropriately sized buffers.
+#[no_mangle]
+pub unsafe extern "C" fn ed25519_verify_modes(
+  public_key: *const u8,
+  signature: *const u8,
+  message: *const u8,
+  message_len: usize,
+) -> c_int {
+  if public_key.is_null() || signature.is_null() {
+    return -1;
+  }
+
+  // Allow null message only if length is 0
+  if message.is_null() && message_len > 0 {
+    return -1;
+  }
+
+  let pk_bytes: [u8; PUBLIC_KEY_LENGTH] =
+    match std::slice::from_raw_parts(public_key, PUBLIC_KEY_LENGTH).try_into() {
+      Ok(bytes) => bytes,
+      Err(_) => return -1,
+    };
+
+  let sig_bytes: [u8; SIGNATURE_LENGTH] =
+    match std::slice::from_raw_parts(signature, SIGNATURE_LENGTH).try_into() {
+      Ok(bytes) => bytes,
+      Err(_) => return -1,
+    };
+
+  let msg = if message_len == 0 {
+    &[]
+  } else {
+    std::slice::from_raw_parts(message, message_len)
+  };
+
+  let modes = verify_modes(&pk_bytes, &sig_bytes, msg);
+  let mut mask = 0;
+  if modes.zip215 {
+    mask |= ED25519_ZIP215_VALID;
+  }
+  if modes.rfc8032 {
+    mask |= ED25519_RFC8032_VALID;
+  }
+  if modes.libsodium {
+    mask |= ED25519_LIBSODIUM_VALID;
+  }
+  mask
+}
+
+/// Batch-verify Ed25519 signatures under ZIP-215 rules.
+///
+/// The whole batch is checked with a single multiscalar multiplication. Only
+/// when it fails is each entry re-verified on its own, to find the bad ones.
+/// Returns `Err` with the indices of the invalid entries, in ascending order.
+pub fn verify_batch(
+  items: &[([u8; PUBLIC_KEY_LENGTH], [u8; SIGNATURE_LENGTH], &[u8])],
+) -> Result<(), Vec<usize>> {
+  let mut verifier = batch::Verifier::new();
+  for (pk, sig, msg) in items {
+    verifier.queue((
+      VerificationKeyBytes::from(*pk),
+      Signature::from(*sig),
+      msg,
+    ));
+  }
+
+  if verifier.verify(rand::thread_rng()).is_ok() {
+    return Ok(());
+  }
+
+  let invalid: Vec<usize> = items
+    .iter()
+    .enumerate()
+    .filter(|(_, (pk, sig, msg))| {
+      VerificationKey::try_from(*pk)
+        .and_then(|vk| vk.verify(&Signature::from(*sig), msg))
+        .is_err()
+    })
+    .map(|(i, _)| i)
+    .collect();
+
+  Err(invalid)
+}
+
+/// Verify a batch of Ed25519 signatures using ZIP-215 compliant validation
+/// rules.
+///
+/// Intended for block import, where a block carries many assurance, guarantee
+/// and judgement signatures that are all expected to be valid.
+///
+/// # Arguments
+/// * `public_keys` - `count` concatenated 32-byte Ed25519 public keys
+/// * `signatures` - `count` concatenated 64-byte Ed25519 signatures
+/// * `messages` - Array of `count` message pointers
+/// * `message_lens` - Array of `count` message lengths
+/// * `count` - Number of entries in the batch
+/// * `results` - Optional output array of `count` bytes; when non-null, entry
+///   `i` is set to `1` if signature `i` is valid and `0` otherwise
+///
+/// # Returns
+/// * `0` - All signatures are valid
+/// * `-1` - At least one signature is invalid or inputs are malformed
+///
+/// # Safety
+/// Caller must ensure all pointers are valid and point to appropriately sized
+/// buffers. A message pointer may only be null if its length is 0.
+#[no_mangle]
+pub unsafe extern "C" fn ed25519_verify_batch(
+  public_keys: *const u8,
+  signatures: *const u8,
+  messages: *const *const u8,
+  message_lens: *const usize,
+  count: usize,
+  results: *mut u8,
+) -> c_int {
+  if count == 0 {
+    return 0;
+  }
+
+  if public_keys.is_null()
+    || signatures.is_null()
+    || messages.is_null()
+    || message_lens.is_null()
+  {
+    return -1;
+  }
+
+  let keys = std::slice::from_raw_parts(public_keys, count * PUBLIC_KEY_LENGTH);
+  let sigs = std::slice::from_raw_parts(signatures, count * SIGNATURE_LENGTH);
+  let msg_ptrs = std::slice::from_raw_parts(messages, count);
+  let msg_lens = std::slice::from_raw_parts(message_lens, count);
+
+  let mut items = Vec::with_capacity(count);
+  for i in 0..count {
+    let (ptr, len) = (msg_ptrs[i], msg_lens[i]);
+    // Allow null message only if length is 0
+    if ptr.is_null() && len > 0 {
+      return -1;
+    }
+    let msg: &[u8] = if len == 0 {
+      &[]
+    } else {
+      std::slice::from_raw_parts(ptr, len)
+    };
+
+    let pk: [u8; PUBLIC_KEY_LENGTH] = keys
+      [i * PUBLIC_KEY_LENGTH..(i + 1) * PUBLIC_KEY_LENGTH]
+      .try_into()
+      .expect("slice has public key length");
+    let sig: [u8; SIGNATURE_LENGTH] = sigs
+      [i * SIGNATURE_LENGTH..(i + 1) * SIGNATURE_LENGTH]
+      .try_into()
+      .expect("slice has signature length");
+    items.push((pk, sig, msg));
+  }
+
+  let outcome = verify_batch(&items);
+
+  if !results.is_null() {
+    let out = std::slice::from_raw_parts_mut(results, count);
+    out.fill(1);
+    if let Err(invalid) = &outcome {
+      for &i in invalid {
+        out[i] = 0;
+      }
+    }
+  }
+
+  match outcome {
+    Ok(()) => 0,
+    Err(_) => -1,
+  }
+}
+
 #[cfg(test)]
 mod tests {
   use super::*;
-  use ed25519_consensus::SigningKey;
   use rand::thread_rng;
 
   #[test]
@@ -223,4 +718,383 @@ mod tests {
 
     assert_eq!(result, 0, "Empty message signature should verify");
   }
+
+  fn signed_batch(count: usize) -> (Vec<u8>, Vec<u8>, Vec<Vec<u8>>) {
+    let mut keys = Vec::new();
+    let mut sigs = Vec::new();
+    let mut msgs = Vec::new();
+    for i in 0..count {
+      let sk = SigningKey::new(thread_rng());
+      let msg = format!("batch message {i}").into_bytes();
+      keys.extend_from_slice(VerificationKey::from(&sk).as_ref());
+      sigs.extend_from_slice(&<[u8; 64]>::from(sk.sign(&msg)));
+      msgs.push(msg);
+    }
+    (keys, sigs, msgs)
+  }
+
+  unsafe fn ffi_verify_batch(
+    keys: &[u8],
+    sigs: &[u8],
+    msgs: &[Vec<u8>],
+    results: &mut [u8],
+  ) -> c_int {
+    let ptrs: Vec<*const u8> = msgs.iter().map(|m| m.as_ptr()).collect();
+    let lens: Vec<usize> = msgs.iter().map(|m| m.len()).collect();
+    ed25519_verify_batch(
+      keys.as_ptr(),
+      sigs.as_ptr(),
+      ptrs.as_ptr(),
+      lens.as_ptr(),
+      msgs.len(),
+      results.as_mut_ptr(),
+    )
+  }
+
+  #[test]
+  fn test_verify_batch() {
+    let (keys, sigs, msgs) = signed_batch(16);
+    let mut results = [0u8; 16];
+
+    let result = unsafe { ffi_verify_batch(&keys, &sigs, &msgs, &mut results) };
+
+    assert_eq!(result, 0, "Valid batch should verify");
+    assert_eq!(results, [1u8; 16]);
+  }
+
+  #[test]
+  fn test_verify_batch_reports_invalid_indices() {
+    let (mut keys, mut sigs, mut msgs) = signed_batch(16);
+    // Corrupted signature, wrong message and undecodable key
+    sigs[3 * SIGNATURE_LENGTH] ^= 0xff;
+    msgs[7] = b"different message".to_vec();
+    keys[11 * PUBLIC_KEY_LENGTH..12 * PUBLIC_KEY_LENGTH].fill(0xff);
+    let mut results = [0u8; 16];
+
+    let result = unsafe { ffi_verify_batch(&keys, &sigs, &msgs, &mut results) };
+
+    assert_eq!(result, -1, "Batch with bad entries should fail");
+    for (i, &valid) in results.iter().enumerate() {
+      assert_eq!(valid == 0, [3, 7, 11].contains(&i), "index {i}");
+    }
+  }
+
+  #[test]
+  fn test_verify_batch_empty() {
+    let result = unsafe {
+      ed25519_verify_batch(
+        std::ptr::null(),
+        std::ptr::null(),
+        std::ptr::null(),
+        std::ptr::null(),
+        0,
+        std::ptr::null_mut(),
+      )
+    };
+    assert_eq!(result, 0, "Empty batch is trivially valid");
+  }
+
+  #[test]
+  fn test_keypair_from_seed_and_sign_rfc8032() {
+    // RFC 8032 section 7.1, TEST 1
+    let seed = hex::decode(
+      "9d61b19deffd5a60ba844af492ec2cc44449c5697b326919703bac031cae7f60",
+    )
+    .unwrap();
+    let expected_pk = hex::decode(
+      "d75a980182b10ab7d54bfed3c964073a0ee172f3daa62325af021a68f707511a",
+    )
+    .unwrap();
+    let expected_sig = hex::decode(
+      "e5564300c360ac729086e2cc806e828a84877f1eb8e5d974d873e06522490155\
+       5fb8821590a33bacc61e39701cf9b46bd25bf5f0595bbe24655141438e7a100b",
+    )
+    .unwrap();
+
+    let mut pk = [0u8; 32];
+    let mut sig = [0u8; 64];
+    unsafe {
+      assert_eq!(ed25519_keypair_from_seed(seed.as_ptr(), pk.as_mut_ptr()), 0);
+      assert_eq!(
+        ed25519_sign(seed.as_ptr(), std::ptr::null(), 0, sig.as_mut_ptr()),
+        0
+      );
+    }
+
+    assert_eq!(pk.as_slice(), expected_pk.as_slice());
+    assert_eq!(sig.as_slice(), expected_sig.as_slice());
+  }
+
+  #[test]
+  fn test_sign_then_verify_via_ffi() {
+    let seed = [7u8; 32];
+    let msg = b"jam_guarantee";
+    let mut pk = [0u8; 32];
+    let mut sig = [0u8; 64];
+
+    unsafe {
+      assert_eq!(ed25519_keypair_from_seed(seed.as_ptr(), pk.as_mut_ptr()), 0);
+      assert_eq!(
+        ed25519_sign(seed.as_ptr(), msg.as_ptr(), msg.len(), sig.as_mut_ptr()),
+        0
+      );
+      assert_eq!(
+        ed25519_verify(pk.as_ptr(), sig.as_ptr(), msg.as_ptr(), msg.len()),
+        0,
+        "Signature made via FFI should verify via FFI"
+      );
+    }
+  }
+
+  #[test]
+  fn test_sign_rejects_null_inputs() {
+    let seed = [7u8; 32];
+    let mut sig = [0u8; 64];
+
+    let result = unsafe {
+      ed25519_sign(seed.as_ptr(), std::ptr::null(), 1, sig.as_mut_ptr())
+    };
+    assert_eq!(result, -1, "Null message with non-zero length is malformed");
+
+    let result = unsafe {
+      ed25519_sign(std::ptr::null(), std::ptr::null(), 0, sig.as_mut_ptr())
+    };
+    assert_eq!(result, -1, "Null seed is malformed");
+  }
+
+  /// The 12 edge cases of the ed25519-speccheck suite as
+  /// (message, public key, signature).
+  const SPECCHECK_CASES: [(&str, &str, &str); 12] = [
+    (
+      "8c93255d71dcab10e8f379c26200f3c7bd5f09d9bc3068d3ef4edeb4853022b6",
+      "c7176a703d4dd84fba3c0b760d10670f2a2053fa2c39ccc64ec7fd7792ac03fa",
+      "c7176a703d4dd84fba3c0b760d10670f2a2053fa2c39ccc64ec7fd7792ac037a\
+       0000000000000000000000000000000000000000000000000000000000000000",
+    ),
+    (
+      "9bd9f44f4dcc75bd531b56b2cd280b0bb38fc1cd6d1230e14861d861de092e79",
+      "c7176a703d4dd84fba3c0b760d10670f2a2053fa2c39ccc64ec7fd7792ac03fa",
+      "f7badec5b8abeaf699583992219b7b223f1df3fbbea919844e3f7c554a43dd43\
+       a5bb704786be79fc476f91d3f3f89b03984d8068dcf1bb7dfc6637b45450ac04",
+    ),
+    (
+      "aebf3f2601a0c8c5d39cc7d8911642f740b78168218da8471772b35f9d35b9ab",
+      "f7badec5b8abeaf699583992219b7b223f1df3fbbea919844e3f7c554a43dd43",
+      "c7176a703d4dd84fba3c0b760d10670f2a2053fa2c39ccc64ec7fd7792ac03fa\
+       8c4bd45aecaca5b24fb97bc10ac27ac8751a7dfe1baff8b953ec9f5833ca260e",
+    ),
+    (
+      "9bd9f44f4dcc75bd531b56b2cd280b0bb38fc1cd6d1230e14861d861de092e79",
+      "cdb267ce40c5cd45306fa5d2f29731459387dbf9eb933b7bd5aed9a765b88d4d",
+      "9046a64750444938de19f227bb80485e92b83fdb4b6506c160484c016cc1852f\
+       87909e14428a7a1d62e9f22f3d3ad7802db02eb2e688b6c52fcd6648a98bd009",
+    ),
+    (
+      "e47d62c63f830dc7a6851a0b1f33ae4bb2f507fb6cffec4011eaccd55b53f56c",
+      "cdb267ce40c5cd45306fa5d2f29731459387dbf9eb933b7bd5aed9a765b88d4d",
+      "160a1cb0dc9c0258cd0a7d23e94d8fa878bcb1925f2c64246b2dee1796bed512\
+       5ec6bc982a269b723e0668e540911a9a6a58921d6925e434ab10aa7940551a09",
+    ),
+    (
+      "e47d62c63f830dc7a6851a0b1f33ae4bb2f507fb6cffec4011eaccd55b53f56c",
+      "cdb267ce40c5cd45306fa5d2f29731459387dbf9eb933b7bd5aed9a765b88d4d",
+      "21122a84e0b5fca4052f5b1235c80a537878b38f3142356b2c2384ebad4668b7\
+       e40bc836dac0f71076f9abe3a53f9c03c1ceeeddb658d0030494ace586687405",
+    ),
+    (
+      "85e241a07d148b41e47d62c63f830dc7a6851a0b1f33ae4bb2f507fb6cffec40",
+      "442aad9f089ad9e14647b1ef9099a1ff4798d78589e66f28eca69c11f582a623",
+      "e96f66be976d82e60150baecff9906684aebb1ef181f67a7189ac78ea23b6c0e\
+       547f7690a0e2ddcd04d87dbc3490dc19b3b3052f7ff0538cb68afb369ba3a514",
+    ),
+    (
+      "85e241a07d148b41e47d62c63f830dc7a6851a0b1f33ae4bb2f507fb6cffec40",
+      "442aad9f089ad9e14647b1ef9099a1ff4798d78589e66f28eca69c11f582a623",
+      "8ce5b96c8f26d0ab6c47958c9e68b937104cd36e13c33566acd2fe8d38aa1942\
+       7e71f98a473474f2f13f06f97c20d58cc3f54b8bd0d272f42b695dd7e89a8c22",
+    ),
+    (
+      "9bedc267423725d473888631ebf45988bad3db83851ee85c85e241a07d148b41",
+      "f7badec5b8abeaf699583992219b7b223f1df3fbbea919844e3f7c554a43dd43",
+      "ecffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff7f\
+       03be9678ac102edcd92b0210bb34d7428d12ffc5df5f37e359941266a4e35f0f",
+    ),
+    (
+      "9bedc267423725d473888631ebf45988bad3db83851ee85c85e241a07d148b41",
+      "f7badec5b8abeaf699583992219b7b223f1df3fbbea919844e3f7c554a43dd43",
+      "ecffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff\
+       ca8c5b64cd208982aa38d4936621a4775aa233aa0505711d8fdcfdaa943d4908",
+    ),
+    (
+      "e96b7021eb39c1a163b6da4e3093dcd3f21387da4cc4572be588fafae23c155b",
+      "ecffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff",
+      "a9d55260f765261eb9b84e106f665e00b867287a761990d7135963ee0a7d59dc\
+       a5bb704786be79fc476f91d3f3f89b03984d8068dcf1bb7dfc6637b45450ac04",
+    ),
+    (
+      "39a591f5321bbe07fd5a23dc2f39d025d74526615746727ceefd6e82ae65c06f",
+      "ecffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff",
+      "a9d55260f765261eb9b84e106f665e00b867287a761990d7135963ee0a7d59dc\
+       a5bb704786be79fc476f91d3f3f89b03984d8068dcf1bb7dfc6637b45450ac04",
+    ),
+  ];
+
+  /// Expected (ZIP-215, RFC 8032, libsodium) outcome per speccheck case.
+  ///
+  /// 0-2: small-order A and/or R; 3: mixed-order A and R; 4-5: pass only the
+  /// cofactored equation; 6-7: S >= L; 8-9: small-order R, canonical and
+  /// non-canonical; 10-11: non-canonical small-order A.
+  const SPECCHECK_EXPECTED: [(bool, bool, bool); 12] = [
+    (true, true, false),
+    (true, true, false),
+    (true, true, false),
+    (true, true, true),
+    (true, false, false),
+    (true, false, false),
+    (false, false, false),
+    (false, false, false),
+    (true, true, false),
+    (true, false, false),
+    (true, false, false),
+    (true, false, false),
+  ];
+
+  #[test]
+  fn test_verify_modes_speccheck() {
+    for (i, ((msg, pk, sig), expected)) in
+      SPECCHECK_CASES.iter().zip(SPECCHECK_EXPECTED).enumerate()
+    {
+      let msg = hex::decode(msg).unwrap();
+      let pk = hex::decode(pk).unwrap();
+      let sig = hex::decode(sig).unwrap();
+
+      let mask = unsafe {
+        ed25519_verify_modes(pk.as_ptr(), sig.as_ptr(), msg.as_ptr(), msg.len())
+      };
+      let actual = (
+        mask & ED25519_ZIP215_VALID != 0,
+        mask & ED25519_RFC8032_VALID != 0,
+        mask & ED25519_LIBSODIUM_VALID != 0,
+      );
+      assert_eq!(actual, expected, "speccheck case {i}");
+
+      // The ZIP-215 bit must agree with ed25519_verify
+      let zip215 = unsafe {
+        ed25519_verify(pk.as_ptr(), sig.as_ptr(), msg.as_ptr(), msg.len())
+      };
+      assert_eq!(zip215 == 0, expected.0, "speccheck case {i}");
+    }
+  }
+
+  #[test]
+  fn test_verify_modes_regular_signature() {
+    let sk = SigningKey::new(thread_rng());
+    let vk: [u8; 32] = VerificationKey::from(&sk).into();
+    let msg = b"test message";
+    let mut sig: [u8; 64] = sk.sign(msg).into();
+
+    assert_eq!(
+      verify_modes(&vk, &sig, msg),
+      VerifyModes {
+        zip215: true,
+        rfc8032: true,
+        libsodium: true
+      }
+    );
+
+    sig[0] ^= 0xff;
+    let modes = verify_modes(&vk, &sig, msg);
+    assert!(!modes.zip215 && !modes.rfc8032 && !modes.libsodium);
+  }
+
+  #[test]
+  fn test_sign_and_verify_with_context() {
+    let seed = [3u8; 32];
+    let report_hash = [0xabu8; 32];
+    let mut pk = [0u8; 32];
+    let mut sig = [0u8; 64];
+
+    unsafe {
+      assert_eq!(ed25519_keypair_from_seed(seed.as_ptr(), pk.as_mut_ptr()), 0);
+      assert_eq!(
+        ed25519_sign_with_context(
+          seed.as_ptr(),
+          SigningContext::Valid as u8,
+          report_hash.as_ptr(),
+          report_hash.len(),
+          sig.as_mut_ptr(),
+        ),
+        0
+      );
+
+      // Same as verifying the hand-built message
+      let message = [b"jam_valid".as_slice(), &report_hash].concat();
+      assert_eq!(
+        ed25519_verify(
+          pk.as_ptr(),
+          sig.as_ptr(),
+          message.as_ptr(),
+          message.len()
+        ),
+        0
+      );
+
+      assert_eq!(
+        ed25519_verify_with_context(
+          pk.as_ptr(),
+          sig.as_ptr(),
+          SigningContext::Valid as u8,
+          report_hash.as_ptr(),
+          report_hash.len(),
+        ),
+        0,
+        "Signature should verify under its own context"
+      );
+      assert_eq!(
+        ed25519_verify_with_context(
+          pk.as_ptr(),
+          sig.as_ptr(),
+          SigningContext::Invalid as u8,
+          report_hash.as_ptr(),
+          report_hash.len(),
+        ),
+        -1,
+        "Signature must not verify under a different context"
+      );
+    }
+  }
+
+  #[test]
+  fn test_unknown_context_rejected() {
+    let seed = [3u8; 32];
+    let mut sig = [0u8; 64];
+
+    let result = unsafe {
+      ed25519_sign_with_context(
+        seed.as_ptr(),
+        5,
+        std::ptr::null(),
+        0,
+        sig.as_mut_ptr(),
+      )
+    };
+    assert_eq!(result, -1, "Context 5 does not exist");
+  }
+
+  #[test]
+  fn test_context_prefixes() {
+    let prefixes: Vec<&[u8]> = (0..=4)
+      .map(|c| SigningContext::try_from(c).unwrap().prefix())
+      .collect();
+    assert_eq!(
+      prefixes,
+      [
+        b"jam_available".as_slice(),
+        b"jam_guarantee",
+        b"jam_announce",
+        b"jam_valid",
+        b"jam_invalid"
+      ]
+    );
+  }
 }
@@ -0,0 +1,521 @@
+//! Prepared Ed25519 validator sets
+//!
+//! The active (kappa) and previous (lambda) validator sets only change once per
+//! epoch, while assurances, guarantees and judgements are checked against them
+//! many times per block. A `ValidatorSet` parses and decompresses every key
+//! once so that per-signature checks can skip that work.
+
+use curve25519_dalek_ng::{
+  constants::ED25519_BASEPOINT_POINT,
+  edwards::{CompressedEdwardsY, EdwardsPoint},
+  scalar::Scalar,
+  traits::{IsIdentity, VartimeMultiscalarMul},
+};
+use ed25519_consensus::{Signature, VerificationKey};
+use libc::{c_int, size_t};
+use rand::Rng;
+use sha2::{Digest, Sha512};
+use std::collections::BTreeMap;
+use std::convert::TryFrom;
+use std::iter::once;
+use thiserror::Error;
+
+const PUBLIC_KEY_LENGTH: usize = 32;
+const SIGNATURE_LENGTH: usize = 64;
+
+#[derive(Error, Debug, PartialEq, Eq)]
+pub enum ValidatorSetError {
+  #[error("Invalid validator index")]
+  InvalidValidatorIndex,
+  #[error("Validator public key is not a valid Ed25519 point")]
+  InvalidPublicKey,
+  #[error("Signature verification failed")]
+  VerificationFailed,
+}
+
+/// Validator key as used by single and batch verification.
+struct PreparedKey {
+  key: VerificationKey,
+  point: EdwardsPoint,
+}
+
+/// Ed25519 keys of a validator set, decompressed once on construction.
+///
+/// Keys that do not decode are kept as empty slots so that indices stay
+/// aligned with the on-chain validator set; any signature checked against such
+/// a slot is rejected.
+pub struct ValidatorSet {
+  keys: Vec<Option<PreparedKey>>,
+}
+
+impl ValidatorSet {
+  /// Creates a validator set from its 32-byte Ed25519 public keys.
+  pub fn new(public_keys: &[[u8; PUBLIC_KEY_LENGTH]]) -> Self {
+    let keys = public_keys
+      .iter()
+      .map(|pk| {
+        Some(PreparedKey {
+          key: VerificationKey::try_from(*pk).ok()?,
+          point: CompressedEdwardsY(*pk).decompress()?,
+        })
+      })
+      .collect();
+    Self { keys }
+  }
+
+  /// Number of validators in the set.
+  pub fn len(&self) -> usize {
+    self.keys.len()
+  }
+
+  /// Returns `true` if the set holds no validators.
+  pub fn is_empty(&self) -> bool {
+    self.keys.is_empty()
+  }
+
+  fn key(&self, index: usize) -> Result<&PreparedKey, ValidatorSetError> {
+    self
+      .keys
+      .get(index)
+      .ok_or(ValidatorSetError::InvalidValidatorIndex)?
+      .as_ref()
+      .ok_or(ValidatorSetError::InvalidPublicKey)
+  }
+
+  /// Verifies a signature made by the validator at `index` under ZIP-215
+  /// rules.
+  pub fn verify(
+    &self,
+    index: usize,
+    signature: &[u8; SIGNATURE_LENGTH],
+    message: &[u8],
+  ) -> Result<(), ValidatorSetError> {
+    self
+      .key(index)?
+      .key
+      .verify(&Signature::from(*signature), message)
+      .map_err(|_| ValidatorSetError::VerificationFailed)
+  }
+
+  /// Batch-verifies `(validator index, signature, message)` entries.
+  ///
+  /// Entries naming an unknown index or an undecodable key are rejected up
+  /// front. The rest are checked with a single batch equation over the
+  /// prepared key points, and only if that fails is each entry re-verified
+  /// against its prepared key. Returns `Err` with the positions (into
+  /// `items`) of the invalid entries, in ascending order.
+  pub fn verify_batch(
+    &self,
+    items: &[(usize, [u8; SIGNATURE_LENGTH], &[u8])],
+  ) -> Result<(), Vec<usize>> {
+    let mut invalid = Vec::new();
+    let mut queued = Vec::with_capacity(items.len());
+    for (pos, item) in items.iter().enumerate() {
+      match self.key(item.0) {
+        Ok(key) => queued.push((key, &item.1, item.2)),
+        Err(_) => invalid.push(pos),
+      }
+    }
+
+    if batch_equation_holds(&queued) {
+      return if invalid.is_empty() {
+        Ok(())
+      } else {
+        Err(invalid)
+      };
+    }
+
+    let invalid = items
+      .iter()
+      .enumerate()
+      .filter(|(_, (index, sig, msg))| self.verify(*index, sig, msg).is_err())
+      .map(|(pos, _)| pos)
+      .collect();
+
+    Err(invalid)
+  }
+}
+
+/// Checks the ZIP-215 batch equation
+/// `[8]([-sum z_i s_i]B + sum [z_i]R_i + sum [z_i k_i]A_i) = 0` for random
+/// 128-bit `z_i`.
+///
+/// This is the equation `ed25519_consensus::batch` checks, but its verifier
+/// only accepts key bytes and would decompress every `A_i` again; here the
+/// points come from the prepared keys. Coefficients of the same validator are
+/// summed so each key appears once in the multiscalar multiplication.
+fn batch_equation_holds(
+  items: &[(&PreparedKey, &[u8; SIGNATURE_LENGTH], &[u8])],
+) -> bool {
+  let mut rng = rand::thread_rng();
+  let mut b_coeff = Scalar::zero();
+  let mut a_terms: BTreeMap<[u8; PUBLIC_KEY_LENGTH], (Scalar, &EdwardsPoint)> =
+    BTreeMap::new();
+  let mut r_coeffs = Vec::with_capacity(items.len());
+  let mut r_points = Vec::with_capacity(items.len());
+
+  for (prepared, signature, message) in items {
+    let r_bytes: [u8; 32] = signature[..32].try_into().expect("R is 32 bytes");
+    let s_bytes: [u8; 32] = signature[32..].try_into().expect("S is 32 bytes");
+    let Some(r) = CompressedEdwardsY(r_bytes).decompress() else {
+      return false;
+    };
+    let Some(s) = Scalar::from_canonical_bytes(s_bytes) else {
+      return false;
+    };
+
+    let a_bytes = prepared.key.to_bytes();
+    let k = Scalar::from_hash(
+      Sha512::default()
+        .chain(r_bytes)
+        .chain(a_bytes)
+        .chain(message),
+    );
+    let z = Scalar::from(rng.gen::<u128>());
+
+    b_coeff -= z * s;
+    a_terms
+      .entry(a_bytes)
+      .or_insert((Scalar::zero(), &prepared.point))
+      .0 += z * k;
+    r_coeffs.push(z);
+    r_points.push(r);
+  }
+
+  let check = EdwardsPoint::vartime_multiscalar_mul(
+    once(&b_coeff)
+      .chain(a_terms.values().map(|(coeff, _)| coeff))
+      .chain(&r_coeffs),
+    once(&ED25519_BASEPOINT_POINT)
+      .chain(a_terms.values().map(|(_, point)| *point))
+      .chain(&r_points),
+  );
+
+  check.mul_by_cofactor().is_identity()
+}
+
+/// Create a prepared Ed25519 validator set.
+///
+/// The set size is determined by the number of public keys passed
+/// (public_keys_len / 32). Keys that fail to decode are kept as slots that
+/// reject every signature.
+///
+/// # Safety
+/// - `public_keys` must point to a contiguous array of 32-byte public keys
+#[no_mangle]
+pub unsafe extern "C" fn new_ed25519_validator_set(
+  public_keys: *const u8,
+  public_keys_len: size_t,
+) -> *mut ValidatorSet {
+  if public_keys.is_null() || !public_keys_len.is_multiple_of(PUBLIC_KEY_LENGTH)
+  {
+    return std::ptr::null_mut();
+  }
+
+  let keys: Vec<[u8; PUBLIC_KEY_LENGTH]> =
+    std::slice::from_raw_parts(public_keys, public_keys_len)
+      .chunks_exact(PUBLIC_KEY_LENGTH)
+      .map(|chunk| chunk.try_into().expect("chunk has public key length"))
+      .collect();
+
+  Box::into_raw(Box::new(ValidatorSet::new(&keys)))
+}
+
+/// Free a prepared Ed25519 validator set.
+///
+/// # Safety
+/// - `set` must be a valid pointer returned by new_ed25519_validator_set
+#[no_mangle]
+pub unsafe extern "C" fn free_ed25519_validator_set(set: *mut ValidatorSet) {
+  debug_assert!(!set.is_null(), "set pointer must not be null");
+  drop(Box::from_raw(set));
+}
+
+/// Verify a signature by the validator at `validator_index`.
+///
+/// # Returns
+/// * `0` - Signature is valid
+/// * `-1` - Signature is invalid, the index is out of range, the validator's
+///   key is undecodable, or inputs are malformed
+///
+/// # Safety
+/// - `set` must be a valid pointer returned by new_ed25519_validator_set
+/// - `signature` must point to 64 bytes
+/// - `message` must point to `message_len` bytes (may be null if 0)
+#[no_mangle]
+pub unsafe extern "C" fn ed25519_validator_set_verify(
+  set: *const ValidatorSet,
+  validator_index: size_t,
+  signature: *const u8,
+  message: *const u8,
+  message_len: size_t,
+) -> c_int {
+  debug_assert!(!set.is_null(), "set pointer must not be null");
+  if signature.is_null() || (message.is_null() && message_len > 0) {
+    return -1;
+  }
+
+  let sig: [u8; SIGNATURE_LENGTH] =
+    match std::slice::from_raw_parts(signature, SIGNATURE_LENGTH).try_into() {
+      Ok(bytes) => bytes,
+      Err(_) => return -1,
+    };
+  let msg = if message_len == 0 {
+    &[]
+  } else {
+    std::slice::from_raw_parts(message, message_len)
+  };
+
+  match (*set).verify(validator_index, &sig, msg) {
+    Ok(()) => 0,
+    Err(_) => -1,
+  }
+}
+
+/// Batch-verify signatures by validators of a prepared set.
+///
+/// # Arguments
+/// * `set` - Prepared validator set
+/// * `validator_indices` - Array of `count` validator indices
+/// * `signatures` - `count` concatenated 64-byte signatures
+/// * `messages` - Array of `count` message pointers
+/// * `message_lens` - Array of `count` message lengths
+/// * `count` - Number of entries in the batch
+/// * `results` - Optional output array of `count` bytes; when non-null, entry
+///   `i` is set to `1` if entry `i` is valid and `0` otherwise
+///
+/// # Returns
+/// * `0` - All signatures are valid
+/// * `-1` - At least one entry is invalid or inputs are malformed
+///
+/// # Safety
+/// - `set` must be a valid pointer returned by new_ed25519_validator_set
+/// - All array pointers must be valid for `count` entries
+#[no_mangle]
+pub unsafe extern "C" fn ed25519_validator_set_verify_batch(
+  set: *const ValidatorSet,
+  validator_indices: *const size_t,
+  signatures: *const u8,
+  messages: *const *const u8,
+  message_lens: *const size_t,
+  count: size_t,
+  results: *mut u8,
+) -> c_int {
+  debug_assert!(!set.is_null(), "set pointer must not be null");
+  if count == 0 {
+    return 0;
+  }
+
+  if validator_indices.is_null()
+    || signatures.is_null()
+    || messages.is_null()
+    || message_lens.is_null()
+  {
+    return -1;
+  }
+
+  let indices = std::slice::from_raw_parts(validator_indices, count);
+  let sigs = std::slice::from_raw_parts(signatures, count * SIGNATURE_LENGTH);
+  let msg_ptrs = std::slice::from_raw_parts(messages, count);
+  let msg_lens = std::slice::from_raw_parts(message_lens, count);
+
+  let mut items = Vec::with_capacity(count);
+  for i in 0..count {
+    let (ptr, len) = (msg_ptrs[i], msg_lens[i]);
+    if ptr.is_null() && len > 0 {
+      return -1;
+    }
+    let msg: &[u8] = if len == 0 {
+      &[]
+    } else {
+      std::slice::from_raw_parts(ptr, len)
+    };
+
+    let sig: [u8; SIGNATURE_LENGTH] = sigs
+      [i * SIGNATURE_LENGTH..(i + 1) * SIGNATURE_LENGTH]
+      .try_into()
+      .expect("slice has signature length");
+    items.push((indices[i], sig, msg));
+  }
+
+  let outcome = (*set).verify_batch(&items);
+
+  if !results.is_null() {
+    let out = std::slice::from_raw_parts_mut(results, count);
+    out.fill(1);
+    if let Err(invalid) = &outcome {
+      for &i in invalid {
+        out[i] = 0;
+      }
+    }
+  }
+
+  match outcome {
+    Ok(()) => 0,
+    Err(_) => -1,
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use ed25519_consensus::SigningKey;
+  use rand::thread_rng;
+
+  fn validators(count: usize) -> (Vec<SigningKey>, Vec<u8>) {
+    let sks: Vec<SigningKey> =
+      (0..count).map(|_| SigningKey::new(thread_rng())).collect();
+    let keys = sks
+      .iter()
+      .flat_map(|sk| sk.verification_key().to_bytes())
+      .collect();
+    (sks, keys)
+  }
+
+  #[test]
+  fn test_verify_by_index() {
+    let (sks, keys) = validators(6);
+    let msg = b"jam_available";
+    let sig: [u8; 64] = sks[4].sign(msg).into();
+
+    unsafe {
+      let set = new_ed25519_validator_set(keys.as_ptr(), keys.len());
+      assert!(!set.is_null());
+      assert_eq!((*set).len(), 6);
+
+      let ok = ed25519_validator_set_verify(
+        set,
+        4,
+        sig.as_ptr(),
+        msg.as_ptr(),
+        msg.len(),
+      );
+      assert_eq!(ok, 0, "Signature by validator 4 should verify");
+
+      let wrong_index = ed25519_validator_set_verify(
+        set,
+        3,
+        sig.as_ptr(),
+        msg.as_ptr(),
+        msg.len(),
+      );
+      assert_eq!(wrong_index, -1, "Signature is not by validator 3");
+
+      let out_of_range = ed25519_validator_set_verify(
+        set,
+        6,
+        sig.as_ptr(),
+        msg.as_ptr(),
+        msg.len(),
+      );
+      assert_eq!(out_of_range, -1, "Index 6 is out of range");
+
+      free_ed25519_validator_set(set);
+    }
+  }
+
+  #[test]
+  fn test_undecodable_key_keeps_indices_aligned() {
+    let (sks, mut keys) = validators(3);
+    // y = 2 is not the y-coordinate of any curve point
+    keys[32..64].fill(0);
+    keys[32] = 2;
+    let set = unsafe { new_ed25519_validator_set(keys.as_ptr(), keys.len()) };
+    let set = unsafe { Box::from_raw(set) };
+
+    let msg = b"jam_guarantee";
+    let sig: [u8; 64] = sks[2].sign(msg).into();
+    assert_eq!(set.len(), 3);
+    assert_eq!(set.verify(2, &sig, msg), Ok(()));
+    assert_eq!(
+      set.verify(1, &sig, msg),
+      Err(ValidatorSetError::InvalidPublicKey)
+    );
+  }
+
+  #[test]
+  fn test_batch_equation_on_prepared_keys() {
+    let (sks, keys) = validators(4);
+    let keys: Vec<[u8; 32]> = keys
+      .chunks_exact(32)
+      .map(|k| k.try_into().unwrap())
+      .collect();
+    let set = ValidatorSet::new(&keys);
+
+    let msgs: Vec<Vec<u8>> = (0..8)
+      .map(|i| format!("judgement {i}").into_bytes())
+      .collect();
+    // Every validator signs twice, so keys repeat within the batch
+    let mut sigs: Vec<[u8; 64]> = msgs
+      .iter()
+      .enumerate()
+      .map(|(i, m)| sks[i % 4].sign(m).into())
+      .collect();
+    let batch = |sigs: &[[u8; 64]]| {
+      let items: Vec<_> = sigs
+        .iter()
+        .zip(&msgs)
+        .enumerate()
+        .map(|(i, (sig, msg))| (set.key(i % 4).unwrap(), sig, &msg[..]))
+        .collect();
+      batch_equation_holds(&items)
+    };
+
+    assert!(batch(&sigs));
+    assert!(batch(&[]));
+
+    sigs[5][40] ^= 1;
+    assert!(!batch(&sigs));
+  }
+
+  #[test]
+  fn test_verify_batch_by_index() {
+    let (sks, keys) = validators(6);
+    let msgs: Vec<Vec<u8>> = (0..6)
+      .map(|i| format!("assurance {i}").into_bytes())
+      .collect();
+    let indices: Vec<usize> = vec![5, 0, 3, 1, 2, 4];
+    let mut sigs: Vec<u8> = indices
+      .iter()
+      .zip(&msgs)
+      .flat_map(|(&v, m)| <[u8; 64]>::from(sks[v].sign(m)))
+      .collect();
+    let ptrs: Vec<*const u8> = msgs.iter().map(|m| m.as_ptr()).collect();
+    let lens: Vec<usize> = msgs.iter().map(|m| m.len()).collect();
+    let mut results = [0u8; 6];
+
+    unsafe {
+      let set = new_ed25519_validator_set(keys.as_ptr(), keys.len());
+
+      let ok = ed25519_validator_set_verify_batch(
+        set,
+        indices.as_ptr(),
+        sigs.as_ptr(),
+        ptrs.as_ptr(),
+        lens.as_ptr(),
+        6,
+        results.as_mut_ptr(),
+      );
+      assert_eq!(ok, 0, "Valid batch should verify");
+      assert_eq!(results, [1u8; 6]);
+
+      // Corrupt the entry at position 2 and point position 4 out of range
+      sigs[2 * SIGNATURE_LENGTH] ^= 0xff;
+      let mut indices = indices.clone();
+      indices[4] = 6;
+      let bad = ed25519_validator_set_verify_batch(
+        set,
+        indices.as_ptr(),
+        sigs.as_ptr(),
+        ptrs.as_ptr(),
+        lens.as_ptr(),
+        6,
+        results.as_mut_ptr(),
+      );
+      assert_eq!(bad, -1, "Batch with bad entries should fail");
+      assert_eq!(results, [1, 1, 0, 1, 0, 1]);
+
+      free_ed25519_validator_set(set);
+    }
+  }
+}
@@ -0,0 +1,939 @@
+//! Password-encrypted validator keystore
+//!
+//! Holds Ed25519 and Bandersnatch secrets in a single file, keyed by public
+//! key, so that operators do not have to manage seed files by hand. Secrets
+//! are decrypted straight into Rust-owned memory and never cross the C ABI;
+//! callers sign by public key.
+//!
+//! File layout (all integers little endian):
+//!
+//! ```text
+//! magic "JAMKEYS\0" (8) ++ version (1) ++ argon2id m_cost, t_cost, p_cost
+//! (3 x u32) ++ salt (16) ++ nonce (24) ++ XChaCha20-Poly1305 ciphertext
+//! ```
+//!
+//! The header up to and including the nonce is authenticated as associated
+//! data. The plaintext is a sequence of `key type (1) ++ secret seed (32)`
+//! entries, where the seed is what `ed25519_sign` resp.
+//! `bandersnatch_secret_from_seed` take.
+
+use std::ffi::{c_char, CStr};
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use argon2::{Algorithm, Argon2, Params, Version};
+use ark_serialize::CanonicalSerialize;
+use ark_vrf::suites::bandersnatch::Secret;
+use chacha20poly1305::aead::{AeadInPlace, KeyInit};
+use chacha20poly1305::{XChaCha20Poly1305, XNonce};
+use ed25519_consensus::SigningKey;
+use libc::{c_int, size_t};
+use rand::RngCore;
+use thiserror::Error;
+use zeroize::Zeroizing;
+
+use crate::sign::{bandersnatch_sign_impl, BandersnatchSecret};
+
+const MAGIC: &[u8; 8] = b"JAMKEYS\0";
+const VERSION: u8 = 1;
+const SALT_LENGTH: usize = 16;
+const NONCE_LENGTH: usize = 24;
+const TAG_LENGTH: usize = 16;
+const HEADER_LENGTH: usize = MAGIC.len() + 1 + 12 + SALT_LENGTH + NONCE_LENGTH;
+const SEED_LENGTH: usize = 32;
+const ENTRY_LENGTH: usize = 1 + SEED_LENGTH;
+const PUBLIC_KEY_LENGTH: usize = 32;
+const ED25519_SIGNATURE_LENGTH: usize = 64;
+const BANDERSNATCH_SIGNATURE_LENGTH: usize = 96;
+
+#[derive(Error, Debug)]
+pub enum KeystoreError {
+  #[error(transparent)]
+  Io(#[from] std::io::Error),
+  #[error("Keystore file already exists")]
+  AlreadyExists,
+  #[error("Not a keystore file")]
+  InvalidFormat,
+  #[error("Unsupported keystore version {0}")]
+  UnsupportedVersion(u8),
+  #[error("Key derivation failed: {0}")]
+  Kdf(String),
+  #[error("Key derivation parameters exceed the supported maximum")]
+  KdfParamsTooHigh,
+  #[error("Wrong password or corrupted keystore")]
+  Decryption,
+  #[error("Encryption failed")]
+  Encryption,
+  #[error("Unknown key type {0}")]
+  UnknownKeyType(u8),
+  #[error("Key not found")]
+  KeyNotFound,
+}
+
+/// Kind of secret held in the keystore.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(u8)]
+pub enum KeyType {
+  Ed25519 = 0,
+  Bandersnatch = 1,
+}
+
+impl TryFrom<u8> for KeyType {
+  type Error = KeystoreError;
+
+  fn try_from(value: u8) -> Result<Self, Self::Error> {
+    match value {
+      0 => Ok(KeyType::Ed25519),
+      1 => Ok(KeyType::Bandersnatch),
+      other => Err(KeystoreError::UnknownKeyType(other)),
+    }
+  }
+}
+
+/// Argon2id cost parameters used to derive the file encryption key.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct KdfParams {
+  /// Memory cost in KiB
+  pub m_cost: u32,
+  /// Number of iterations
+  pub t_cost: u32,
+  /// Degree of parallelism
+  pub p_cost: u32,
+}
+
+impl KdfParams {
+  /// Largest accepted memory cost, 1 GiB
+  pub const MAX_M_COST: u32 = 1 << 20;
+  /// Largest accepted number of iterations
+  pub const MAX_T_COST: u32 = 16;
+  /// Largest accepted degree of parallelism
+  pub const MAX_P_COST: u32 = 16;
+
+  /// Rejects costs above the maximums. The header is only authenticated
+  /// after the key is derived, so without this a tampered file could make
+  /// `open` allocate arbitrary amounts of memory.
+  fn check(self) -> Result<Self, KeystoreError> {
+    if self.m_cost > Self::MAX_M_COST
+      || self.t_cost > Self::MAX_T_COST
+      || self.p_cost > Self::MAX_P_COST
+    {
+      return Err(KeystoreError::KdfParamsTooHigh);
+    }
+    Ok(self)
+  }
+}
+
+impl Default for KdfParams {
+  fn default() -> Self {
+    Self {
+      m_cost: Params::DEFAULT_M_COST,
+      t_cost: Params::DEFAULT_T_COST,
+      p_cost: Params::DEFAULT_P_COST,
+    }
+  }
+}
+
+enum Entry {
+  Ed25519 {
+    seed: Zeroizing<[u8; SEED_LENGTH]>,
+    public: [u8; PUBLIC_KEY_LENGTH],
+  },
+  Bandersnatch {
+    seed: Zeroizing<[u8; SEED_LENGTH]>,
+    secret: Secret,
+    public: [u8; PUBLIC_KEY_LENGTH],
+  },
+}
+
+impl Entry {
+  fn new(key_type: KeyType, seed: [u8; SEED_LENGTH]) -> Self {
+    let seed = Zeroizing::new(seed);
+    match key_type {
+      KeyType::Ed25519 => {
+        let public = SigningKey::from(*seed).verification_key().to_bytes();
+        Entry::Ed25519 { seed, public }
+      }
+      KeyType::Bandersnatch => {
+        let secret = Secret::from_seed(&seed[..]);
+        let mut public = [0u8; PUBLIC_KEY_LENGTH];
+        secret
+          .public()
+          .serialize_compressed(&mut public[..])
+          .expect("public key is 32 bytes");
+        Entry::Bandersnatch {
+          seed,
+          secret,
+          public,
+        }
+      }
+    }
+  }
+
+  fn key_type(&self) -> KeyType {
+    match self {
+      Entry::Ed25519 { .. } => KeyType::Ed25519,
+      Entry::Bandersnatch { .. } => KeyType::Bandersnatch,
+    }
+  }
+
+  fn public(&self) -> &[u8; PUBLIC_KEY_LENGTH] {
+    match self {
+      Entry::Ed25519 { public, .. } | Entry::Bandersnatch { public, .. } => {
+        public
+      }
+    }
+  }
+
+  fn seed(&self) -> &[u8; SEED_LENGTH] {
+    match self {
+      Entry::Ed25519 { seed, .. } | Entry::Bandersnatch { seed, .. } => seed,
+    }
+  }
+}
+
+/// Validator secrets decrypted from a keystore file.
+///
+/// Every change is written back to the file straight away, re-encrypted under
+/// the same password with a fresh nonce.
+pub struct Keystore {
+  path: PathBuf,
+  params: KdfParams,
+  salt: [u8; SALT_LENGTH],
+  key: Zeroizing<[u8; 32]>,
+  entries: Vec<Entry>,
+}
+
+fn derive_key(
+  password: &[u8],
+  salt: &[u8; SALT_LENGTH],
+  params: KdfParams,
+) -> Result<Zeroizing<[u8; 32]>, KeystoreError> {
+  let params = params.check()?;
+  let params =
+    Params::new(params.m_cost, params.t_cost, params.p_cost, Some(32))
+      .map_err(|e| KeystoreError::Kdf(e.to_string()))?;
+  let mut key = Zeroizing::new([0u8; 32]);
+  Argon2::new(Algorithm::Argon2id, Version::V0x13, params)
+    .hash_password_into(password, salt, &mut key[..])
+    .map_err(|e| KeystoreError::Kdf(e.to_string()))?;
+  Ok(key)
+}
+
+/// Writes `contents` to a new file at `path` that only the owner can read.
+fn write_private(path: &Path, contents: &[u8]) -> std::io::Result<()> {
+  let mut options = fs::OpenOptions::new();
+  options.write(true).create_new(true);
+  #[cfg(unix)]
+  std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+
+  // A temporary file left by an interrupted save may have other permissions
+  match fs::remove_file(path) {
+    Err(e) if e.kind() != std::io::ErrorKind::NotFound => return Err(e),
+    _ => {}
+  }
+  let mut file = options.open(path)?;
+  file.write_all(contents)?;
+  file.sync_all()
+}
+
+fn encode_header(
+  params: KdfParams,
+  salt: &[u8; SALT_LENGTH],
+  nonce: &[u8; NONCE_LENGTH],
+) -> Vec<u8> {
+  let mut header = Vec::with_capacity(HEADER_LENGTH);
+  header.extend_from_slice(MAGIC);
+  header.push(VERSION);
+  header.extend_from_slice(&params.m_cost.to_le_bytes());
+  header.extend_from_slice(&params.t_cost.to_le_bytes());
+  header.extend_from_slice(&params.p_cost.to_le_bytes());
+  header.extend_from_slice(salt);
+  header.extend_from_slice(nonce);
+  header
+}
+
+impl Keystore {
+  /// Creates a new, empty keystore file with default KDF parameters.
+  pub fn create(
+    path: impl AsRef<Path>,
+    password: &[u8],
+  ) -> Result<Self, KeystoreError> {
+    Self::create_with_params(path, password, KdfParams::default())
+  }
+
+  /// Creates a new, empty keystore file with the given KDF parameters.
+  pub fn create_with_params(
+    path: impl AsRef<Path>,
+    password: &[u8],
+    params: KdfParams,
+  ) -> Result<Self, KeystoreError> {
+    let path = path.as_ref().to_path_buf();
+    if path.exists() {
+      return Err(KeystoreError::AlreadyExists);
+    }
+
+    let mut salt = [0u8; SALT_LENGTH];
+    rand::thread_rng().fill_bytes(&mut salt);
+    let key = derive_key(password, &salt, params)?;
+
+    let keystore = Self {
+      path,
+      params,
+      salt,
+      key,
+      entries: Vec::new(),
+    };
+    keystore.save()?;
+    Ok(keystore)
+  }
+
+  /// Opens and decrypts an existing keystore file.
+  pub fn open(
+    path: impl AsRef<Path>,
+    password: &[u8],
+  ) -> Result<Self, KeystoreError> {
+    let path = path.as_ref().to_path_buf();
+    let bytes = fs::read(&path)?;
+    if bytes.len() < HEADER_LENGTH || &bytes[..MAGIC.len()] != MAGIC {
+      return Err(KeystoreError::InvalidFormat);
+    }
+
+    let (header, ciphertext) = bytes.split_at(HEADER_LENGTH);
+    let version = header[MAGIC.len()];
+    if version != VERSION {
+      return Err(KeystoreError::UnsupportedVersion(version));
+    }
+
+    let u32_at = |offset: usize| {
+      u32::from_le_bytes(header[offset..offset + 4].try_into().unwrap())
+    };
+    let params = KdfParams {
+      m_cost: u32_at(9),
+      t_cost: u32_at(13),
+      p_cost: u32_at(17),
+    };
+    let salt: [u8; SALT_LENGTH] = header[21..21 + SALT_LENGTH]
+      .try_into()
+      .expect("header has salt length");
+    let nonce = &header[21 + SALT_LENGTH..];
+
+    let key = derive_key(password, &salt, params)?;
+    let mut plaintext = Zeroizing::new(ciphertext.to_vec());
+    XChaCha20Poly1305::new((&*key).into())
+      .decrypt_in_place(XNonce::from_slice(nonce), header, &mut *plaintext)
+      .map_err(|_| KeystoreError::Decryption)?;
+
+    if !plaintext.len().is_multiple_of(ENTRY_LENGTH) {
+      return Err(KeystoreError::InvalidFormat);
+    }
+    let entries = plaintext
+      .chunks_exact(ENTRY_LENGTH)
+      .map(|chunk| {
+        let key_type = KeyType::try_from(chunk[0])?;
+        let seed = chunk[1..].try_into().expect("entry has seed length");
+        Ok(Entry::new(key_type, seed))
+      })
+      .collect::<Result<_, KeystoreError>>()?;
+
+    Ok(Self {
+      path,
+      params,
+      salt,
+      key,
+      entries,
+    })
+  }
+
+  /// Re-encrypts all entries and atomically replaces the keystore file.
+  fn save(&self) -> Result<(), KeystoreError> {
+    let mut nonce = [0u8; NONCE_LENGTH];
+    rand::thread_rng().fill_bytes(&mut nonce);
+    let header = encode_header(self.params, &self.salt, &nonce);
+
+    // Sized up front, tag included, so the seeds are never left behind in
+    // a reallocated buffer
+    let mut buffer = Zeroizing::new(Vec::with_capacity(
+      self.entries.len() * ENTRY_LENGTH + TAG_LENGTH,
+    ));
+    for entry in &self.entries {
+      buffer.push(entry.key_type() as u8);
+      buffer.extend_from_slice(entry.seed());
+    }
+    XChaCha20Poly1305::new((&*self.key).into())
+      .encrypt_in_place(XNonce::from_slice(&nonce), &header, &mut *buffer)
+      .map_err(|_| KeystoreError::Encryption)?;
+
+    // Appended rather than swapped for the extension, so that `keys.a` and
+    // `keys.b` never share a temporary file
+    let mut tmp = self.path.as_os_str().to_owned();
+    tmp.push(".tmp");
+    write_private(Path::new(&tmp), &[&header[..], &buffer[..]].concat())?;
+    fs::rename(&tmp, &self.path)?;
+    Ok(())
+  }
+
+  /// Adds a secret seed and persists the keystore. Returns its public key.
+  ///
+  /// Importing a key that is already present is a no-op. If the keystore
+  /// cannot be saved, the key is not added.
+  pub fn import(
+    &mut self,
+    key_type: KeyType,
+    seed: [u8; SEED_LENGTH],
+  ) -> Result<[u8; PUBLIC_KEY_LENGTH], KeystoreError> {
+    let entry = Entry::new(key_type, seed);
+    let public = *entry.public();
+    if self.find(key_type, &public).is_none() {
+      self.entries.push(entry);
+      if let Err(e) = self.save() {
+        self.entries.pop();
+        return Err(e);
+      }
+    }
+    Ok(public)
+  }
+
+  /// Public keys of the given type, in import order.
+  pub fn public_keys(&self, key_type: KeyType) -> Vec<[u8; PUBLIC_KEY_LENGTH]> {
+    self
+      .entries
+      .iter()
+      .filter(|entry| entry.key_type() == key_type)
+      .map(|entry| *entry.public())
+      .collect()
+  }
+
+  fn find(
+    &self,
+    key_type: KeyType,
+    public: &[u8; PUBLIC_KEY_LENGTH],
+  ) -> Option<&Entry> {
+    self
+      .entries
+      .iter()
+      .find(|entry| entry.key_type() == key_type && entry.public() == public)
+  }
+
+  /// Signs `message` with the Ed25519 key for `public`.
+  pub fn sign_ed25519(
+    &self,
+    public: &[u8; PUBLIC_KEY_LENGTH],
+    message: &[u8],
+  ) -> Result<[u8; ED25519_SIGNATURE_LENGTH], KeystoreError> {
+    match self.find(KeyType::Ed25519, public) {
+      Some(Entry::Ed25519 { seed, .. }) => {
+        Ok(SigningKey::from(**seed).sign(message).into())
+      }
+      _ => Err(KeystoreError::KeyNotFound),
+    }
+  }
+
+  /// Creates a Bandersnatch IETF VRF signature with the key for `public`,
+  /// as `bandersnatch_sign` does.
+  pub fn sign_bandersnatch(
+    &self,
+    public: &[u8; PUBLIC_KEY_LENGTH],
+    vrf_input_data: &[u8],
+    context_data: &[u8],
+  ) -> Result<[u8; BANDERSNATCH_SIGNATURE_LENGTH], KeystoreError> {
+    let secret = self.bandersnatch_secret(public)?;
+    let signature =
+      bandersnatch_sign_impl(secret, vrf_input_data, context_data);
+    let mut out = [0u8; BANDERSNATCH_SIGNATURE_LENGTH];
+    signature
+      .serialize_compressed(&mut out[..])
+      .expect("signature is 96 bytes");
+    Ok(out)
+  }
+
+  /// Bandersnatch secret for `public`, e.g. to build a ring VRF prover.
+  pub fn bandersnatch_secret(
+    &self,
+    public: &[u8; PUBLIC_KEY_LENGTH],
+  ) -> Result<&Secret, KeystoreError> {
+    match self.find(KeyType::Bandersnatch, public) {
+      Some(Entry::Bandersnatch { secret, .. }) => Ok(secret),
+      _ => Err(KeystoreError::KeyNotFound),
+    }
+  }
+}
+
+unsafe fn keystore_args<'a>(
+  path: *const c_char,
+  password: *const u8,
+  password_len: size_t,
+) -> Option<(&'a str, &'a [u8])> {
+  if path.is_null() || (password.is_null() && password_len > 0) {
+    return None;
+  }
+  let path = CStr::from_ptr(path).to_str().ok()?;
+  let password = if password_len == 0 {
+    &[]
+  } else {
+    std::slice::from_raw_parts(password, password_len)
+  };
+  Some((path, password))
+}
+
+unsafe fn read_public<'a>(
+  public_key: *const u8,
+) -> Option<&'a [u8; PUBLIC_KEY_LENGTH]> {
+  if public_key.is_null() {
+    return None;
+  }
+  std::slice::from_raw_parts(public_key, PUBLIC_KEY_LENGTH)
+    .try_into()
+    .ok()
+}
+
+/// Creates a new, empty keystore file and returns a handle to it.
+///
+/// Returns null if the file already exists or cannot be written.
+///
+/// # Safety
+/// - `path` must be a valid NUL-terminated string
+/// - `password` must point to `password_len` bytes (may be null if 0)
+#[no_mangle]
+pub unsafe extern "C" fn keystore_create(
+  path: *const c_char,
+  password: *const u8,
+  password_len: size_t,
+) -> *mut Keystore {
+  let Some((path, password)) = keystore_args(path, password, password_len)
+  else {
+    return std::ptr::null_mut();
+  };
+  match Keystore::create(path, password) {
+    Ok(keystore) => Box::into_raw(Box::new(keystore)),
+    Err(_) => std::ptr::null_mut(),
+  }
+}
+
+/// Opens and decrypts an existing keystore file.
+///
+/// Returns null if the file is missing, malformed or the password is wrong.
+///
+/// # Safety
+/// - `path` must be a valid NUL-terminated string
+/// - `password` must point to `password_len` bytes (may be null if 0)
+#[no_mangle]
+pub unsafe extern "C" fn keystore_open(
+  path: *const c_char,
+  password: *const u8,
+  password_len: size_t,
+) -> *mut Keystore {
+  let Some((path, password)) = keystore_args(path, password, password_len)
+  else {
+    return std::ptr::null_mut();
+  };
+  match Keystore::open(path, password) {
+    Ok(keystore) => Box::into_raw(Box::new(keystore)),
+    Err(_) => std::ptr::null_mut(),
+  }
+}
+
+/// Frees a keystore handle, zeroizing the secrets it holds.
+///
+/// # Safety
+/// - `keystore` must be a valid pointer returned by keystore_create or
+///   keystore_open
+#[no_mangle]
+pub unsafe extern "C" fn free_keystore(keystore: *mut Keystore) {
+  debug_assert!(!keystore.is_null(), "keystore pointer must not be null");
+  drop(Box::from_raw(keystore));
+}
+
+/// Imports a secret seed of `key_type` (0 = Ed25519, 1 = Bandersnatch) and
+/// writes its 32-byte public key to `public_key_out`.
+///
+/// Returns 0 on success, -1 on error
+///
+/// # Safety
+/// - `keystore` must be a valid keystore handle
+/// - `seed` must point to 32 bytes, `public_key_out` to 32 writable bytes
+#[no_mangle]
+pub unsafe extern "C" fn keystore_import(
+  keystore: *mut Keystore,
+  key_type: u8,
+  seed: *const u8,
+  public_key_out: *mut u8,
+) -> c_int {
+  debug_assert!(!keystore.is_null(), "keystore pointer must not be null");
+  if seed.is_null() || public_key_out.is_null() {
+    return -1;
+  }
+  let Ok(key_type) = KeyType::try_from(key_type) else {
+    return -1;
+  };
+  let seed: [u8; SEED_LENGTH] = std::slice::from_raw_parts(seed, SEED_LENGTH)
+    .try_into()
+    .expect("slice has seed length");
+
+  match (*keystore).import(key_type, seed) {
+    Ok(public) => {
+      std::ptr::copy_nonoverlapping(
+        public.as_ptr(),
+        public_key_out,
+        PUBLIC_KEY_LENGTH,
+      );
+      0
+    }
+    Err(_) => -1,
+  }
+}
+
+/// Lists the public keys of `key_type` (0 = Ed25519, 1 = Bandersnatch).
+///
+/// Writes up to `capacity` concatenated 32-byte keys to `public_keys_out` and
+/// the total number of keys of that type to `count_out`, so callers can size
+/// the buffer with a first call using `capacity = 0`.
+///
+/// Returns 0 on success, -1 on error
+///
+/// # Safety
+/// - `keystore` must be a valid keystore handle
+/// - `public_keys_out` must point to `capacity * 32` writable bytes (may be
+///   null if `capacity` is 0)
+/// - `count_out` must point to a writable `size_t`
+#[no_mangle]
+pub unsafe extern "C" fn keystore_list(
+  keystore: *const Keystore,
+  key_type: u8,
+  public_keys_out: *mut u8,
+  capacity: size_t,
+  count_out: *mut size_t,
+) -> c_int {
+  debug_assert!(!keystore.is_null(), "keystore pointer must not be null");
+  if count_out.is_null() || (public_keys_out.is_null() && capacity > 0) {
+    return -1;
+  }
+  let Ok(key_type) = KeyType::try_from(key_type) else {
+    return -1;
+  };
+
+  let keys = (*keystore).public_keys(key_type);
+  for (i, key) in keys.iter().take(capacity).enumerate() {
+    std::ptr::copy_nonoverlapping(
+      key.as_ptr(),
+      public_keys_out.add(i * PUBLIC_KEY_LENGTH),
+      PUBLIC_KEY_LENGTH,
+    );
+  }
+  *count_out = keys.len();
+  0
+}
+
+/// Signs a message with the stored Ed25519 key for `public_key`.
+///
+/// Writes the 64-byte signature to `signature_out`.
+/// Returns 0 on success, -1 if the key is not in the keystore or on error
+///
+/// # Safety
+/// - `keystore` must be a valid keystore handle
+/// - `public_key` must point to 32 bytes, `signature_out` to 64 writable bytes
+/// - `message` must point to `message_len` bytes (may be null if 0)
+#[no_mangle]
+pub unsafe extern "C" fn keystore_sign_ed25519(
+  keystore: *const Keystore,
+  public_key: *const u8,
+  message: *const u8,
+  message_len: size_t,
+  signature_out: *mut u8,
+) -> c_int {
+  debug_assert!(!keystore.is_null(), "keystore pointer must not be null");
+  let Some(public) = read_public(public_key) else {
+    return -1;
+  };
+  if signature_out.is_null() || (message.is_null() && message_len > 0) {
+    return -1;
+  }
+  let message = if message_len == 0 {
+    &[]
+  } else {
+    std::slice::from_raw_parts(message, message_len)
+  };
+
+  match (*keystore).sign_ed25519(public, message) {
+    Ok(signature) => {
+      std::ptr::copy_nonoverlapping(
+        signature.as_ptr(),
+        signature_out,
+        ED25519_SIGNATURE_LENGTH,
+      );
+      0
+    }
+    Err(_) => -1,
+  }
+}
+
+/// Creates a Bandersnatch IETF VRF signature with the stored key for
+/// `public_key`, as bandersnatch_sign does.
+///
+/// Writes the 96-byte signature to `signature_out`.
+/// Returns 0 on success, -1 if the key is not in the keystore or on error
+///
+/// # Safety
+/// - `keystore` must be a valid keystore handle
+/// - `public_key` must point to 32 bytes, `signature_out` to 96 writable bytes
+/// - `vrf_input_data` and `context_data` must be valid for their lengths
+#[no_mangle]
+pub unsafe extern "C" fn keystore_sign_bandersnatch(
+  keystore: *const Keystore,
+  public_key: *const u8,
+  vrf_input_data: *const u8,
+  vrf_input_len: size_t,
+  context_data: *const u8,
+  context_len: size_t,
+  signature_out: *mut u8,
+) -> c_int {
+  debug_assert!(!keystore.is_null(), "keystore pointer must not be null");
+  let Some(public) = read_public(public_key) else {
+    return -1;
+  };
+  if vrf_input_data.is_null()
+    || context_data.is_null()
+    || signature_out.is_null()
+  {
+    return -1;
+  }
+  let vrf_input = std::slice::from_raw_parts(vrf_input_data, vrf_input_len);
+  let context = std::slice::from_raw_parts(context_data, context_len);
+
+  match (*keystore).sign_bandersnatch(public, vrf_input, context) {
+    Ok(signature) => {
+      std::ptr::copy_nonoverlapping(
+        signature.as_ptr(),
+        signature_out,
+        BANDERSNATCH_SIGNATURE_LENGTH,
+      );
+      0
+    }
+    Err(_) => -1,
+  }
+}
+
+/// Returns a Bandersnatch secret handle for the stored key of `public_key`,
+/// for use with new_ring_vrf_prover_with_secret. The secret stays inside
+/// Rust; free the handle with bandersnatch_free_secret.
+///
+/// Returns null if the key is not in the keystore.
+///
+/// # Safety
+/// - `keystore` must be a valid keystore handle
+/// - `public_key` must point to 32 bytes
+#[no_mangle]
+pub unsafe extern "C" fn keystore_bandersnatch_secret(
+  keystore: *const Keystore,
+  public_key: *const u8,
+) -> *mut BandersnatchSecret {
+  debug_assert!(!keystore.is_null(), "keystore pointer must not be null");
+  let Some(public) = read_public(public_key) else {
+    return std::ptr::null_mut();
+  };
+  match (*keystore).bandersnatch_secret(public) {
+    Ok(secret) => Box::into_raw(Box::new(BandersnatchSecret(secret.clone()))),
+    Err(_) => std::ptr::null_mut(),
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::ed25519::ed25519_verify;
+  use crate::sign::bandersnatch_verify;
+  use std::ffi::CString;
+
+  // Keep tests fast; production keystores use KdfParams::default()
+  const TEST_PARAMS: KdfParams = KdfParams {
+    m_cost: 64,
+    t_cost: 1,
+    p_cost: 1,
+  };
+
+  fn test_keystore(dir: &tempfile::TempDir) -> Keystore {
+    Keystore::create_with_params(
+      dir.path().join("keys"),
+      b"hunter2",
+      TEST_PARAMS,
+    )
+    .unwrap()
+  }
+
+  #[test]
+  fn test_import_and_reopen() {
+    let dir = tempfile::tempdir().unwrap();
+    let mut keystore = test_keystore(&dir);
+    let ed = keystore.import(KeyType::Ed25519, [1; 32]).unwrap();
+    let bs = keystore.import(KeyType::Bandersnatch, [2; 32]).unwrap();
+    // Re-importing is a no-op
+    keystore.import(KeyType::Ed25519, [1; 32]).unwrap();
+    drop(keystore);
+
+    let keystore = Keystore::open(dir.path().join("keys"), b"hunter2").unwrap();
+    assert_eq!(keystore.public_keys(KeyType::Ed25519), vec![ed]);
+    assert_eq!(keystore.public_keys(KeyType::Bandersnatch), vec![bs]);
+  }
+
+  #[test]
+  fn test_failed_import_is_not_kept() {
+    let dir = tempfile::tempdir().unwrap();
+    let mut keystore = test_keystore(&dir);
+
+    // A directory in the way of the temporary file makes the save fail
+    let blocker = dir.path().join("keys.tmp");
+    fs::create_dir(&blocker).unwrap();
+    assert!(keystore.import(KeyType::Ed25519, [1; 32]).is_err());
+    assert!(keystore.public_keys(KeyType::Ed25519).is_empty());
+
+    // Retrying once the save can succeed persists the key
+    fs::remove_dir(&blocker).unwrap();
+    let ed = keystore.import(KeyType::Ed25519, [1; 32]).unwrap();
+    let keystore = Keystore::open(dir.path().join("keys"), b"hunter2").unwrap();
+    assert_eq!(keystore.public_keys(KeyType::Ed25519), vec![ed]);
+  }
+
+  #[test]
+  fn test_wrong_password_and_tampering_rejected() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("keys");
+    let mut keystore = test_keystore(&dir);
+    keystore.import(KeyType::Ed25519, [1; 32]).unwrap();
+
+    assert!(matches!(
+      Keystore::open(&path, b"hunter3"),
+      Err(KeystoreError::Decryption)
+    ));
+
+    // The header is authenticated, so changing the KDF cost to other valid
+    // parameters is detected
+    let mut bytes = fs::read(&path).unwrap();
+    bytes[9] ^= 1;
+    fs::write(&path, &bytes).unwrap();
+    assert!(matches!(
+      Keystore::open(&path, b"hunter2"),
+      Err(KeystoreError::Decryption)
+    ));
+
+    assert!(matches!(
+      Keystore::create_with_params(&path, b"hunter2", TEST_PARAMS),
+      Err(KeystoreError::AlreadyExists)
+    ));
+  }
+
+  #[test]
+  fn test_inflated_kdf_cost_rejected_before_derivation() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("keys");
+    test_keystore(&dir);
+
+    // A 4 TiB memory cost would abort the process if it reached argon2
+    let mut bytes = fs::read(&path).unwrap();
+    bytes[9..13].copy_from_slice(&u32::MAX.to_le_bytes());
+    fs::write(&path, &bytes).unwrap();
+    assert!(matches!(
+      Keystore::open(&path, b"hunter2"),
+      Err(KeystoreError::KdfParamsTooHigh)
+    ));
+  }
+
+  #[cfg(unix)]
+  #[test]
+  fn test_keystore_file_is_private() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let dir = tempfile::tempdir().unwrap();
+    let mut keystore = test_keystore(&dir);
+    keystore.import(KeyType::Ed25519, [1; 32]).unwrap();
+
+    let mode = fs::metadata(dir.path().join("keys"))
+      .unwrap()
+      .permissions()
+      .mode();
+    assert_eq!(mode & 0o777, 0o600);
+  }
+
+  #[test]
+  fn test_sign_by_public_key_via_ffi() {
+    let dir = tempfile::tempdir().unwrap();
+    let mut keystore = test_keystore(&dir);
+    let ed = keystore.import(KeyType::Ed25519, [1; 32]).unwrap();
+    let bs = keystore.import(KeyType::Bandersnatch, [2; 32]).unwrap();
+    drop(keystore);
+
+    let path = CString::new(dir.path().join("keys").to_str().unwrap()).unwrap();
+    let password = b"hunter2";
+    let msg = b"jam_guarantee";
+    let mut ed_sig = [0u8; 64];
+    let mut bs_sig = [0u8; 96];
+    let mut output_hash = [0u8; 32];
+    let mut listed = [0u8; 32];
+    let mut count = 0usize;
+
+    unsafe {
+      let handle =
+        keystore_open(path.as_ptr(), password.as_ptr(), password.len());
+      assert!(!handle.is_null());
+
+      assert_eq!(
+        keystore_list(handle, 0, listed.as_mut_ptr(), 1, &mut count),
+        0
+      );
+      assert_eq!((count, listed), (1, ed));
+
+      assert_eq!(
+        keystore_sign_ed25519(
+          handle,
+          ed.as_ptr(),
+          msg.as_ptr(),
+          msg.len(),
+          ed_sig.as_mut_ptr()
+        ),
+        0
+      );
+      assert_eq!(
+        ed25519_verify(ed.as_ptr(), ed_sig.as_ptr(), msg.as_ptr(), msg.len()),
+        0
+      );
+
+      assert_eq!(
+        keystore_sign_bandersnatch(
+          handle,
+          bs.as_ptr(),
+          msg.as_ptr(),
+          msg.len(),
+          msg.as_ptr(),
+          0,
+          bs_sig.as_mut_ptr()
+        ),
+        0
+      );
+      assert_eq!(
+        bandersnatch_verify(
+          bs.as_ptr(),
+          msg.as_ptr(),
+          msg.len(),
+          msg.as_ptr(),
+          0,
+          bs_sig.as_ptr(),
+          output_hash.as_mut_ptr()
+        ),
+        0
+      );
+
+      // Keys are typed: the Bandersnatch key cannot make Ed25519 signatures
+      assert_eq!(
+        keystore_sign_ed25519(
+          handle,
+          bs.as_ptr(),
+          msg.as_ptr(),
+          msg.len(),
+          ed_sig.as_mut_ptr()
+        ),
+        -1
+      );
+
+      free_keystore(handle);
+    }
+  }
+}
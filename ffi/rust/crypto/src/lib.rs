@@ -1,3 +1,7 @@
 pub mod ed25519;
+pub mod ed25519_validator_set;
+pub mod keystore;
+pub mod remote_signer;
 pub mod ring_vrf;
 pub mod sign;
+pub mod validator_keys;
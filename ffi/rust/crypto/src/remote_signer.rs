@@ -0,0 +1,803 @@
+//! Out-of-process signing over a Unix domain socket
+//!
+//! The `jamzig-signer` binary holds a `Keystore` and serves signing requests
+//! on a Unix socket, so validator secrets never enter the node process. The
+//! node talks to it through the `remote_signer_*` exports, which mirror the
+//! local signing calls and take the signer's public key instead of a secret.
+//!
+//! Every message is a frame of `length (u32 LE) ++ body`. Request bodies are
+//! `op (1) ++ public key (32)` followed by the op's fields, with variable
+//! length fields prefixed by their length as u32 LE:
+//!
+//! - `0` Ed25519: `context (1) ++ payload`, signed as
+//!   `SigningContext::message(payload)`
+//! - `1` Bandersnatch IETF VRF (seal, entropy): `vrf_input ++ aux`
+//! - `2` Ring VRF (tickets): `ring keys ++ vrf_input ++ aux`; the signer finds
+//!   its own position in the ring
+//!
+//! Response bodies are `0 ++ signature` on success and `1 ++ UTF-8 message`
+//! on failure. A connection may carry any number of request/response pairs.
+
+use std::ffi::{c_char, CStr};
+use std::io::{self, Read, Write};
+use std::net::Shutdown;
+use std::os::unix::net::{UnixListener, UnixStream};
+use std::path::Path;
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+
+use libc::{c_int, size_t};
+use thiserror::Error;
+
+use crate::ed25519::SigningContext;
+use crate::keystore::{Keystore, KeystoreError};
+use crate::ring_vrf::prover::{Prover, ProverError};
+use crate::ring_vrf::types::{ring_from_public_keys, PUBLIC_KEY_SIZE};
+
+/// Upper bound on a frame body; a full 1023-key ring is ~32 KiB.
+const MAX_FRAME_LENGTH: usize = 1 << 20;
+const PUBLIC_KEY_LENGTH: usize = 32;
+const ED25519_SIGNATURE_LENGTH: usize = 64;
+const BANDERSNATCH_SIGNATURE_LENGTH: usize = 96;
+const RING_VRF_SIGNATURE_LENGTH: usize = 784;
+
+/// How long a client waits on each request and response by default. A ring
+/// proof over a full validator set takes well under this.
+pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(10);
+
+const OP_ED25519: u8 = 0;
+const OP_BANDERSNATCH: u8 = 1;
+const OP_RING_VRF: u8 = 2;
+
+const STATUS_OK: u8 = 0;
+const STATUS_ERROR: u8 = 1;
+
+#[derive(Error, Debug)]
+pub enum RemoteSignerError {
+  #[error(transparent)]
+  Io(#[from] io::Error),
+  #[error("Malformed message")]
+  MalformedMessage,
+  #[error("Frame exceeds maximum length")]
+  FrameTooLarge,
+  #[error("Unknown Ed25519 signing context")]
+  UnknownContext,
+  #[error(transparent)]
+  Keystore(#[from] KeystoreError),
+  #[error("Signer key is not in the ring")]
+  NotInRing,
+  #[error("Invalid ring")]
+  InvalidRing,
+  #[error(transparent)]
+  Prover(#[from] ProverError),
+  #[error("Remote signer error: {0}")]
+  Remote(String),
+  #[error("Remote signer did not respond in time")]
+  Timeout,
+}
+
+/// A signing request, as sent from the node to the signer.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Request {
+  Ed25519 {
+    public: [u8; PUBLIC_KEY_LENGTH],
+    context: SigningContext,
+    payload: Vec<u8>,
+  },
+  Bandersnatch {
+    public: [u8; PUBLIC_KEY_LENGTH],
+    vrf_input: Vec<u8>,
+    aux: Vec<u8>,
+  },
+  RingVrf {
+    public: [u8; PUBLIC_KEY_LENGTH],
+    ring: Vec<u8>,
+    vrf_input: Vec<u8>,
+    aux: Vec<u8>,
+  },
+}
+
+fn put_field(out: &mut Vec<u8>, field: &[u8]) {
+  out.extend_from_slice(&(field.len() as u32).to_le_bytes());
+  out.extend_from_slice(field);
+}
+
+/// Cursor over a request body.
+struct Reader<'a>(&'a [u8]);
+
+impl<'a> Reader<'a> {
+  fn take(&mut self, len: usize) -> Result<&'a [u8], RemoteSignerError> {
+    if self.0.len() < len {
+      return Err(RemoteSignerError::MalformedMessage);
+    }
+    let (head, tail) = self.0.split_at(len);
+    self.0 = tail;
+    Ok(head)
+  }
+
+  fn field(&mut self) -> Result<Vec<u8>, RemoteSignerError> {
+    let len = u32::from_le_bytes(self.take(4)?.try_into().unwrap());
+    Ok(self.take(len as usize)?.to_vec())
+  }
+
+  fn finish(self) -> Result<(), RemoteSignerError> {
+    if self.0.is_empty() {
+      Ok(())
+    } else {
+      Err(RemoteSignerError::MalformedMessage)
+    }
+  }
+}
+
+impl Request {
+  pub fn encode(&self) -> Vec<u8> {
+    let mut out = Vec::new();
+    match self {
+      Request::Ed25519 {
+        public,
+        context,
+        payload,
+      } => {
+        out.push(OP_ED25519);
+        out.extend_from_slice(public);
+        out.push(*context as u8);
+        put_field(&mut out, payload);
+      }
+      Request::Bandersnatch {
+        public,
+        vrf_input,
+        aux,
+      } => {
+        out.push(OP_BANDERSNATCH);
+        out.extend_from_slice(public);
+        put_field(&mut out, vrf_input);
+        put_field(&mut out, aux);
+      }
+      Request::RingVrf {
+        public,
+        ring,
+        vrf_input,
+        aux,
+      } => {
+        out.push(OP_RING_VRF);
+        out.extend_from_slice(public);
+        put_field(&mut out, ring);
+        put_field(&mut out, vrf_input);
+        put_field(&mut out, aux);
+      }
+    }
+    out
+  }
+
+  pub fn decode(body: &[u8]) -> Result<Self, RemoteSignerError> {
+    let mut reader = Reader(body);
+    let op = reader.take(1)?[0];
+    let public = reader.take(PUBLIC_KEY_LENGTH)?.try_into().unwrap();
+    let request = match op {
+      OP_ED25519 => Request::Ed25519 {
+        public,
+        context: SigningContext::try_from(reader.take(1)?[0])
+          .map_err(|_| RemoteSignerError::UnknownContext)?,
+        payload: reader.field()?,
+      },
+      OP_BANDERSNATCH => Request::Bandersnatch {
+        public,
+        vrf_input: reader.field()?,
+        aux: reader.field()?,
+      },
+      OP_RING_VRF => Request::RingVrf {
+        public,
+        ring: reader.field()?,
+        vrf_input: reader.field()?,
+        aux: reader.field()?,
+      },
+      _ => return Err(RemoteSignerError::MalformedMessage),
+    };
+    reader.finish()?;
+    Ok(request)
+  }
+}
+
+fn write_frame(stream: &mut impl Write, body: &[u8]) -> io::Result<()> {
+  stream.write_all(&(body.len() as u32).to_le_bytes())?;
+  stream.write_all(body)?;
+  stream.flush()
+}
+
+/// Reads one frame. Returns `None` if the peer closed the connection
+/// between frames.
+fn read_frame(
+  stream: &mut impl Read,
+) -> Result<Option<Vec<u8>>, RemoteSignerError> {
+  let mut len = [0u8; 4];
+  match stream.read_exact(&mut len) {
+    Ok(()) => {}
+    Err(e) if e.kind() == io::ErrorKind::UnexpectedEof => return Ok(None),
+    Err(e) => return Err(e.into()),
+  }
+  let len = u32::from_le_bytes(len) as usize;
+  if len > MAX_FRAME_LENGTH {
+    return Err(RemoteSignerError::FrameTooLarge);
+  }
+  let mut body = vec![0u8; len];
+  stream.read_exact(&mut body)?;
+  Ok(Some(body))
+}
+
+/// Executes a request against the keystore, returning the signature.
+pub fn handle_request(
+  keystore: &Keystore,
+  request: &Request,
+) -> Result<Vec<u8>, RemoteSignerError> {
+  match request {
+    Request::Ed25519 {
+      public,
+      context,
+      payload,
+    } => Ok(
+      keystore
+        .sign_ed25519(public, &context.message(payload))?
+        .to_vec(),
+    ),
+    Request::Bandersnatch {
+      public,
+      vrf_input,
+      aux,
+    } => Ok(keystore.sign_bandersnatch(public, vrf_input, aux)?.to_vec()),
+    Request::RingVrf {
+      public,
+      ring,
+      vrf_input,
+      aux,
+    } => {
+      let secret = keystore.bandersnatch_secret(public)?;
+      if !ring.len().is_multiple_of(PUBLIC_KEY_SIZE) {
+        return Err(RemoteSignerError::InvalidRing);
+      }
+      let prover_idx = ring
+        .chunks_exact(PUBLIC_KEY_SIZE)
+        .position(|key| key == public)
+        .ok_or(RemoteSignerError::NotInRing)?;
+      let ring = ring_from_public_keys(ring)
+        .map_err(|_| RemoteSignerError::InvalidRing)?;
+      let prover = Prover::new(ring, secret.clone(), prover_idx);
+      Ok(prover.ring_vrf_sign(vrf_input, aux)?)
+    }
+  }
+}
+
+/// Serves requests on one connection until the peer disconnects.
+///
+/// Requests that cannot be served get an error response; only transport
+/// errors and unparseable frames end the connection.
+pub fn handle_connection(
+  keystore: &Keystore,
+  mut stream: UnixStream,
+) -> Result<(), RemoteSignerError> {
+  while let Some(body) = read_frame(&mut stream)? {
+    let request = Request::decode(&body)?;
+    let response = match handle_request(keystore, &request) {
+      Ok(signature) => [&[STATUS_OK][..], &signature].concat(),
+      Err(e) => [&[STATUS_ERROR][..], e.to_string().as_bytes()].concat(),
+    };
+    write_frame(&mut stream, &response)?;
+  }
+  Ok(())
+}
+
+/// Accepts connections forever, serving each on its own thread.
+///
+/// A connection that ends with an error is passed to `on_error`; failing to
+/// accept a connection ends the loop and is returned.
+pub fn serve<F>(
+  listener: UnixListener,
+  keystore: Arc<Keystore>,
+  on_error: F,
+) -> io::Result<()>
+where
+  F: Fn(RemoteSignerError) + Send + Sync + 'static,
+{
+  let on_error = Arc::new(on_error);
+  for stream in listener.incoming() {
+    let stream = stream?;
+    let keystore = Arc::clone(&keystore);
+    let on_error = Arc::clone(&on_error);
+    thread::spawn(move || {
+      if let Err(e) = handle_connection(&keystore, stream) {
+        on_error(e);
+      }
+    });
+  }
+  Ok(())
+}
+
+/// Client side of a remote signer connection.
+pub struct RemoteSigner {
+  stream: UnixStream,
+}
+
+impl RemoteSigner {
+  /// Connects to a signer listening on `path`, waiting at most
+  /// `DEFAULT_TIMEOUT` on each request.
+  pub fn connect(path: impl AsRef<Path>) -> Result<Self, RemoteSignerError> {
+    Self::connect_with_timeout(path, DEFAULT_TIMEOUT)
+  }
+
+  /// Connects to a signer listening on `path`. Sending a request or reading
+  /// its response fails with `RemoteSignerError::Timeout` once it has taken
+  /// longer than `timeout`.
+  pub fn connect_with_timeout(
+    path: impl AsRef<Path>,
+    timeout: Duration,
+  ) -> Result<Self, RemoteSignerError> {
+    let stream = UnixStream::connect(path)?;
+    stream.set_read_timeout(Some(timeout))?;
+    stream.set_write_timeout(Some(timeout))?;
+    Ok(Self { stream })
+  }
+
+  /// Sends a request and waits for the signature.
+  ///
+  /// After a timeout the connection is shut down, since a late response
+  /// would otherwise be taken as the answer to the next request.
+  pub fn request(
+    &mut self,
+    request: &Request,
+  ) -> Result<Vec<u8>, RemoteSignerError> {
+    let response = match self.exchange(request) {
+      Err(RemoteSignerError::Io(e))
+        if matches!(
+          e.kind(),
+          io::ErrorKind::WouldBlock | io::ErrorKind::TimedOut
+        ) =>
+      {
+        let _ = self.stream.shutdown(Shutdown::Both);
+        return Err(RemoteSignerError::Timeout);
+      }
+      response => response?,
+    };
+    match response.split_first() {
+      Some((&STATUS_OK, signature)) => Ok(signature.to_vec()),
+      Some((&STATUS_ERROR, message)) => Err(RemoteSignerError::Remote(
+        String::from_utf8_lossy(message).into_owned(),
+      )),
+      _ => Err(RemoteSignerError::MalformedMessage),
+    }
+  }
+
+  fn exchange(
+    &mut self,
+    request: &Request,
+  ) -> Result<Vec<u8>, RemoteSignerError> {
+    write_frame(&mut self.stream, &request.encode())?;
+    read_frame(&mut self.stream)?.ok_or(RemoteSignerError::MalformedMessage)
+  }
+
+  fn request_exact<const N: usize>(
+    &mut self,
+    request: &Request,
+  ) -> Result<[u8; N], RemoteSignerError> {
+    self
+      .request(request)?
+      .try_into()
+      .map_err(|_| RemoteSignerError::MalformedMessage)
+  }
+}
+
+/// Copies `len` bytes from a possibly-null pointer.
+unsafe fn to_vec(data: *const u8, len: size_t) -> Option<Vec<u8>> {
+  if len == 0 {
+    Some(Vec::new())
+  } else if data.is_null() {
+    None
+  } else {
+    Some(std::slice::from_raw_parts(data, len).to_vec())
+  }
+}
+
+unsafe fn read_public(
+  public_key: *const u8,
+) -> Option<[u8; PUBLIC_KEY_LENGTH]> {
+  if public_key.is_null() {
+    return None;
+  }
+  std::slice::from_raw_parts(public_key, PUBLIC_KEY_LENGTH)
+    .try_into()
+    .ok()
+}
+
+unsafe fn write_signature<const N: usize>(
+  result: Result<[u8; N], RemoteSignerError>,
+  signature_out: *mut u8,
+) -> c_int {
+  match result {
+    Ok(signature) => {
+      std::ptr::copy_nonoverlapping(signature.as_ptr(), signature_out, N);
+      0
+    }
+    Err(_) => -1,
+  }
+}
+
+/// Connects to a remote signer listening on the Unix socket at `path`.
+///
+/// Each request fails after `timeout_ms` milliseconds without progress, or
+/// after 10 seconds if `timeout_ms` is 0. A timed out connection is closed
+/// and must be replaced.
+///
+/// Returns null if the connection fails.
+///
+/// # Safety
+/// - `path` must be a valid NUL-terminated string
+#[no_mangle]
+pub unsafe extern "C" fn remote_signer_connect(
+  path: *const c_char,
+  timeout_ms: u64,
+) -> *mut RemoteSigner {
+  if path.is_null() {
+    return std::ptr::null_mut();
+  }
+  let Ok(path) = CStr::from_ptr(path).to_str() else {
+    return std::ptr::null_mut();
+  };
+  let timeout = match timeout_ms {
+    0 => DEFAULT_TIMEOUT,
+    ms => Duration::from_millis(ms),
+  };
+  match RemoteSigner::connect_with_timeout(path, timeout) {
+    Ok(signer) => Box::into_raw(Box::new(signer)),
+    Err(_) => std::ptr::null_mut(),
+  }
+}
+
+/// Closes a remote signer connection.
+///
+/// # Safety
+/// - `signer` must be a valid pointer returned by remote_signer_connect
+#[no_mangle]
+pub unsafe extern "C" fn free_remote_signer(signer: *mut RemoteSigner) {
+  debug_assert!(!signer.is_null(), "signer pointer must not be null");
+  drop(Box::from_raw(signer));
+}
+
+/// Remote counterpart of ed25519_sign_with_context, signing with the key for
+/// `public_key`.
+///
+/// Writes the 64-byte signature to `signature_out`.
+/// Returns 0 on success, -1 on transport errors or if the signer refused
+///
+/// # Safety
+/// - `signer` must be a valid remote signer handle
+/// - `public_key` must point to 32 bytes, `signature_out` to 64 writable bytes
+/// - `payload` must point to `payload_len` bytes (may be null if 0)
+#[no_mangle]
+pub unsafe extern "C" fn remote_signer_sign_ed25519_with_context(
+  signer: *mut RemoteSigner,
+  public_key: *const u8,
+  context: u8,
+  payload: *const u8,
+  payload_len: size_t,
+  signature_out: *mut u8,
+) -> c_int {
+  debug_assert!(!signer.is_null(), "signer pointer must not be null");
+  let Some(public) = read_public(public_key) else {
+    return -1;
+  };
+  let Some(payload) = to_vec(payload, payload_len) else {
+    return -1;
+  };
+  let Ok(context) = SigningContext::try_from(context) else {
+    return -1;
+  };
+  if signature_out.is_null() {
+    return -1;
+  }
+
+  let request = Request::Ed25519 {
+    public,
+    context,
+    payload,
+  };
+  write_signature::<ED25519_SIGNATURE_LENGTH>(
+    (*signer).request_exact(&request),
+    signature_out,
+  )
+}
+
+/// Remote counterpart of bandersnatch_sign for seal and entropy signatures,
+/// signing with the key for `public_key`.
+///
+/// Writes the 96-byte signature to `signature_out`.
+/// Returns 0 on success, -1 on transport errors or if the signer refused
+///
+/// # Safety
+/// - `signer` must be a valid remote signer handle
+/// - `public_key` must point to 32 bytes, `signature_out` to 96 writable bytes
+/// - `vrf_input_data` and `aux_data` must be valid for their lengths (may be
+///   null if 0)
+#[no_mangle]
+pub unsafe extern "C" fn remote_signer_sign_bandersnatch(
+  signer: *mut RemoteSigner,
+  public_key: *const u8,
+  vrf_input_data: *const u8,
+  vrf_input_len: size_t,
+  aux_data: *const u8,
+  aux_len: size_t,
+  signature_out: *mut u8,
+) -> c_int {
+  debug_assert!(!signer.is_null(), "signer pointer must not be null");
+  let Some(public) = read_public(public_key) else {
+    return -1;
+  };
+  let (Some(vrf_input), Some(aux)) = (
+    to_vec(vrf_input_data, vrf_input_len),
+    to_vec(aux_data, aux_len),
+  ) else {
+    return -1;
+  };
+  if signature_out.is_null() {
+    return -1;
+  }
+
+  let request = Request::Bandersnatch {
+    public,
+    vrf_input,
+    aux,
+  };
+  write_signature::<BANDERSNATCH_SIGNATURE_LENGTH>(
+    (*signer).request_exact(&request),
+    signature_out,
+  )
+}
+
+/// Remote counterpart of vrf_sign with a ring prover, for ticket proofs.
+///
+/// The signer looks up `public_key` in `public_keys` itself; keys that fail
+/// to decode are padded as in new_ring_vrf_prover. Writes the 784-byte ring
+/// signature to `signature_out`.
+///
+/// Returns 0 on success, -1 on transport errors or if the signer refused
+///
+/// # Safety
+/// - `signer` must be a valid remote signer handle
+/// - `public_key` must point to 32 bytes, `signature_out` to 784 writable
+///   bytes
+/// - `public_keys` must point to a contiguous array of serialized public keys
+/// - `vrf_input_data` and `aux_data` must be valid for their lengths (may be
+///   null if 0)
+#[no_mangle]
+pub unsafe extern "C" fn remote_signer_ring_vrf_sign(
+  signer: *mut RemoteSigner,
+  public_key: *const u8,
+  public_keys: *const u8,
+  public_keys_len: size_t,
+  vrf_input_data: *const u8,
+  vrf_input_len: size_t,
+  aux_data: *const u8,
+  aux_len: size_t,
+  signature_out: *mut u8,
+) -> c_int {
+  debug_assert!(!signer.is_null(), "signer pointer must not be null");
+  let Some(public) = read_public(public_key) else {
+    return -1;
+  };
+  let (Some(ring), Some(vrf_input), Some(aux)) = (
+    to_vec(public_keys, public_keys_len),
+    to_vec(vrf_input_data, vrf_input_len),
+    to_vec(aux_data, aux_len),
+  ) else {
+    return -1;
+  };
+  if signature_out.is_null() {
+    return -1;
+  }
+
+  let request = Request::RingVrf {
+    public,
+    ring,
+    vrf_input,
+    aux,
+  };
+  write_signature::<RING_VRF_SIGNATURE_LENGTH>(
+    (*signer).request_exact(&request),
+    signature_out,
+  )
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::ed25519::ed25519_verify_with_context;
+  use crate::keystore::{KdfParams, KeyType};
+  use crate::ring_vrf::verifier::Verifier;
+  use crate::sign::bandersnatch_verify;
+  use ark_serialize::CanonicalSerialize;
+  use ark_vrf::suites::bandersnatch::Secret;
+  use std::ffi::CString;
+
+  /// Starts a signer on a temporary socket holding one Ed25519 and one
+  /// Bandersnatch key. Returns the socket path, both public keys and a ring
+  /// containing the Bandersnatch key.
+  fn spawn_signer(
+    dir: &tempfile::TempDir,
+  ) -> (CString, [u8; 32], [u8; 32], Vec<u8>) {
+    let params = KdfParams {
+      m_cost: 64,
+      t_cost: 1,
+      p_cost: 1,
+    };
+    let mut keystore =
+      Keystore::create_with_params(dir.path().join("keys"), b"pw", params)
+        .unwrap();
+    let ed = keystore.import(KeyType::Ed25519, [1; 32]).unwrap();
+    let bs = keystore.import(KeyType::Bandersnatch, [2; 32]).unwrap();
+
+    // Ring of four with the signer's key at position 2
+    let mut ring = Vec::new();
+    for seed in [[3u8; 32], [4; 32], [2; 32], [5; 32]] {
+      Secret::from_seed(&seed)
+        .public()
+        .serialize_compressed(&mut ring)
+        .unwrap();
+    }
+    assert_eq!(&ring[64..96], &bs);
+
+    let path = dir.path().join("signer.sock");
+    let listener = UnixListener::bind(&path).unwrap();
+    let keystore = Arc::new(keystore);
+    thread::spawn(move || serve(listener, keystore, |_| {}));
+
+    let path = CString::new(path.to_str().unwrap()).unwrap();
+    (path, ed, bs, ring)
+  }
+
+  #[test]
+  fn test_request_roundtrip() {
+    let request = Request::RingVrf {
+      public: [7; 32],
+      ring: vec![1; 64],
+      vrf_input: b"jam_ticket_seal".to_vec(),
+      aux: Vec::new(),
+    };
+    assert_eq!(Request::decode(&request.encode()).unwrap(), request);
+
+    let mut truncated = request.encode();
+    truncated.pop();
+    assert!(Request::decode(&truncated).is_err());
+  }
+
+  #[test]
+  fn test_unresponsive_signer_times_out() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("stalled.sock");
+    // Never accepted, so requests are buffered but never answered
+    let _listener = UnixListener::bind(&path).unwrap();
+    let request = Request::Bandersnatch {
+      public: [7; 32],
+      vrf_input: b"jam_entropy".to_vec(),
+      aux: Vec::new(),
+    };
+
+    let mut signer =
+      RemoteSigner::connect_with_timeout(&path, Duration::from_millis(50))
+        .unwrap();
+    assert!(matches!(
+      signer.request(&request),
+      Err(RemoteSignerError::Timeout)
+    ));
+    // The connection is closed rather than left out of step
+    assert!(signer.request(&request).is_err());
+  }
+
+  #[test]
+  fn test_remote_signing_over_socket() {
+    let dir = tempfile::tempdir().unwrap();
+    let (path, ed, bs, ring) = spawn_signer(&dir);
+    let payload = b"work report hash";
+    let vrf_input = b"jam_entropy";
+    let mut ed_sig = [0u8; 64];
+    let mut bs_sig = [0u8; 96];
+    let mut ring_sig = [0u8; 784];
+    let mut output_hash = [0u8; 32];
+
+    unsafe {
+      let signer = remote_signer_connect(path.as_ptr(), 0);
+      assert!(!signer.is_null());
+
+      let context = SigningContext::Guarantee as u8;
+      assert_eq!(
+        remote_signer_sign_ed25519_with_context(
+          signer,
+          ed.as_ptr(),
+          context,
+          payload.as_ptr(),
+          payload.len(),
+          ed_sig.as_mut_ptr()
+        ),
+        0
+      );
+      assert_eq!(
+        ed25519_verify_with_context(
+          ed.as_ptr(),
+          ed_sig.as_ptr(),
+          context,
+          payload.as_ptr(),
+          payload.len()
+        ),
+        0
+      );
+
+      assert_eq!(
+        remote_signer_sign_bandersnatch(
+          signer,
+          bs.as_ptr(),
+          vrf_input.as_ptr(),
+          vrf_input.len(),
+          std::ptr::null(),
+          0,
+          bs_sig.as_mut_ptr()
+        ),
+        0
+      );
+      assert_eq!(
+        bandersnatch_verify(
+          bs.as_ptr(),
+          vrf_input.as_ptr(),
+          vrf_input.len(),
+          vrf_input.as_ptr(),
+          0,
+          bs_sig.as_ptr(),
+          output_hash.as_mut_ptr()
+        ),
+        0
+      );
+
+      assert_eq!(
+        remote_signer_ring_vrf_sign(
+          signer,
+          bs.as_ptr(),
+          ring.as_ptr(),
+          ring.len(),
+          vrf_input.as_ptr(),
+          vrf_input.len(),
+          std::ptr::null(),
+          0,
+          ring_sig.as_mut_ptr()
+        ),
+        0
+      );
+      let verifier =
+        Verifier::new(ring_from_public_keys(&ring).unwrap()).unwrap();
+      assert!(verifier.ring_vrf_verify(vrf_input, &[], &ring_sig).is_ok());
+
+      // A key the signer does not hold is refused, and the connection
+      // stays usable afterwards
+      assert_eq!(
+        remote_signer_sign_bandersnatch(
+          signer,
+          ring.as_ptr(),
+          vrf_input.as_ptr(),
+          vrf_input.len(),
+          std::ptr::null(),
+          0,
+          bs_sig.as_mut_ptr()
+        ),
+        -1
+      );
+      assert_eq!(
+        remote_signer_sign_ed25519_with_context(
+          signer,
+          ed.as_ptr(),
+          context,
+          payload.as_ptr(),
+          payload.len(),
+          ed_sig.as_mut_ptr()
+        ),
+        0
+      );
+
+      free_remote_signer(signer);
+    }
+  }
+}
@@ -1,11 +1,19 @@
 use ark_serialize::CanonicalDeserialize;
 use ark_vrf::suites::bandersnatch::*;
+use std::collections::HashSet;
+use std::sync::Arc;
 use thiserror::Error;
 
 use crate::ring_vrf::{
-  context::{ring_context, RingContextError},
-  types::{vrf_input_point, RingCommitment, RingVrfSignature},
+  context::{
+    ring_batch_verifier, ring_context, ring_verifier, RingContextError,
+  },
+  types::{
+    ring_from_public_keys, vrf_input_point, RingCommitment, RingVrfSignature,
+    PUBLIC_KEY_SIZE,
+  },
 };
+use crate::validator_keys::VALIDATOR_DATA_LENGTH;
 
 /// Verify based on Commitment
 ///
@@ -28,6 +36,8 @@ pub enum Error {
   VrfInputPointError,
   #[error(transparent)]
   RingContextError(#[from] RingContextError),
+  #[error("Ring index {0} is out of range")]
+  InvalidRingIndex(usize),
 }
 
 impl Commitment {
@@ -60,37 +70,87 @@ impl Commitment {
   /// # Performance
   /// This method is more efficient than full ring verification since it uses
   /// a cached commitment instead of processing the complete ring of public keys.
+  /// The verifier prepared from the commitment is cached as well (see
+  /// `context::ring_verifier`), so repeated calls against the same gamma_z
+  /// only pay for the proof check.
   pub fn ring_vrf_verify(
     &self,
     vrf_input_data: &[u8],
     aux_data: &[u8],
     signature: &[u8],
   ) -> Result<[u8; 32], Error> {
-    use ark_vrf::ring::Verifier as _;
-
-    let signature = RingVrfSignature::deserialize_compressed(signature)
-      .map_err(|_| Error::DeserializationError)?;
+    let verifier = self.ring_verifier()?;
+    verify_with(&verifier, vrf_input_data, aux_data, signature)
+  }
 
-    let input =
-      vrf_input_point(vrf_input_data).ok_or(Error::VrfInputPointError)?;
-    let output = signature.output;
+  /// Verifies a batch of Ring VRF signatures against this commitment, e.g.
+  /// the tickets of one extrinsic.
+  ///
+  /// # Parameters
+  /// * `items` - `(vrf_input_data, aux_data, signature)` per signature
+  ///
+  /// # Returns
+  /// * `Ok(Vec<Option<[u8; 32]>>)` - The VRF output hash of each signature,
+  ///   in order, or `None` for each signature that fails
+  /// * `Err(Error)` - If no verifier can be set up for the commitment
+  ///
+  /// # Performance
+  /// The signatures that decode are checked together with ark-vrf's batch
+  /// verifier, which folds their pairing checks into one. Only if the batch
+  /// is rejected are they checked one by one, in parallel, to find every
+  /// signature that fails.
+  pub fn ring_vrf_verify_batch(
+    &self,
+    items: &[(&[u8], &[u8], &[u8])],
+  ) -> Result<Vec<Option<[u8; 32]>>, Error> {
+    use rayon::prelude::*;
 
-    let ring_ctx = ring_context(self.ring_size)?;
-    let verifier_key =
-      ring_ctx.verifier_key_from_commitment(self.commitment.clone());
-    let verifier = ring_ctx.verifier(verifier_key);
+    let decoded: Vec<Option<DecodedSignature>> = items
+      .par_iter()
+      .map(|(vrf_input_data, _, signature)| {
+        decode_signature(vrf_input_data, signature).ok()
+      })
+      .collect();
+    if decoded.iter().all(Option::is_none) {
+      return Ok(vec![None; items.len()]);
+    }
 
-    if Public::verify(input, output, aux_data, &signature.proof, &verifier)
-      .is_err()
-    {
-      return Err(Error::SignatureVerificationFailed);
+    let mut batch = ring_batch_verifier(self.ring_size, &self.commitment)?;
+    for ((_, aux_data, _), entry) in items.iter().zip(&decoded) {
+      if let Some((input, signature)) = entry {
+        batch.push(*input, signature.output, aux_data, &signature.proof);
+      }
+    }
+    if batch.verify().is_ok() {
+      return Ok(
+        decoded
+          .iter()
+          .map(|entry| {
+            entry
+              .as_ref()
+              .map(|(_, signature)| output_hash(&signature.output))
+          })
+          .collect(),
+      );
     }
 
-    let vrf_output_hash: [u8; 32] = output.hash()[..32]
-      .try_into()
-      .expect("VRF output hash should be 32 bytes");
+    // At least one proof is bad; find all of them so that only those are
+    // dropped
+    let verifier = self.ring_verifier()?;
+    Ok(
+      items
+        .par_iter()
+        .zip(&decoded)
+        .map(|((_, aux_data, _), entry)| {
+          let (input, signature) = entry.as_ref()?;
+          verify_decoded(&verifier, *input, signature, aux_data).ok()
+        })
+        .collect(),
+    )
+  }
 
-    Ok(vrf_output_hash)
+  fn ring_verifier(&self) -> Result<Arc<RingVerifier>, Error> {
+    Ok(ring_verifier(self.ring_size, &self.commitment)?)
   }
 
   /// Returns a reference to the ring commitment used for verification.
@@ -109,3 +169,273 @@ impl Commitment {
     self.ring_size
   }
 }
+
+/// Computes the commitment to a ring of public keys, as used for gamma_z.
+pub fn ring_commitment(
+  ring: &[Public],
+) -> Result<RingCommitment, RingContextError> {
+  // Backend currently requires the wrapped type (plain affine points)
+  let pts: Vec<_> = ring.iter().map(|pk| pk.0).collect();
+  Ok(ring_context(ring.len())?.verifier_key(&pts).commitment())
+}
+
+/// Computes the commitment to a ring of serialized public keys after setting
+/// the positions in `nulled` to the padding point.
+///
+/// Keys that fail to decode are padded as in `new_ring_vrf_verifier`. Fails
+/// with `Error::InvalidRingIndex` if a position lies outside the ring.
+pub fn ring_commitment_nulling(
+  public_keys: &[u8],
+  nulled: &[usize],
+) -> Result<RingCommitment, Error> {
+  let mut ring = ring_from_public_keys(public_keys)?;
+  let padding_point = Public::from_affine(RingProofParams::padding_point());
+  for &index in nulled {
+    *ring.get_mut(index).ok_or(Error::InvalidRingIndex(index))? = padding_point;
+  }
+  Ok(ring_commitment(&ring)?)
+}
+
+/// Computes gamma_z for a new epoch's validator keys, nulling offenders first.
+///
+/// The Gray Paper (eq. 6.14) replaces the keys of validators whose Ed25519 key
+/// is in the offenders set with the null key before the ring is committed.
+/// Those ring slots are set to the padding point here rather than left to fail
+/// key decoding. Other keys follow the `new_ring_vrf_verifier` rules.
+///
+/// # Parameters
+/// * `validators` - Pending validator keys in the `ValidatorData` layout
+/// * `offenders` - Ed25519 keys of the offenders set
+///
+/// # Returns
+/// The commitment and the ascending indices of the nulled validators.
+pub fn ring_commitment_with_offenders(
+  validators: &[[u8; VALIDATOR_DATA_LENGTH]],
+  offenders: &[[u8; 32]],
+) -> Result<(RingCommitment, Vec<usize>), Error> {
+  let offenders: HashSet<&[u8; 32]> = offenders.iter().collect();
+
+  let keys: Vec<u8> = validators
+    .iter()
+    .flat_map(|validator| validator[..PUBLIC_KEY_SIZE].iter().copied())
+    .collect();
+  let replaced: Vec<usize> = validators
+    .iter()
+    .enumerate()
+    .filter(|(_, validator)| {
+      let ed25519: &[u8; 32] = validator[PUBLIC_KEY_SIZE..2 * PUBLIC_KEY_SIZE]
+        .try_into()
+        .expect("slice has ed25519 key length");
+      offenders.contains(ed25519)
+    })
+    .map(|(i, _)| i)
+    .collect();
+
+  let commitment = ring_commitment_nulling(&keys, &replaced)?;
+  Ok((commitment, replaced))
+}
+
+/// A Ring VRF signature decoded together with its VRF input point.
+type DecodedSignature = (Input, RingVrfSignature);
+
+fn decode_signature(
+  vrf_input_data: &[u8],
+  signature: &[u8],
+) -> Result<DecodedSignature, Error> {
+  let signature = RingVrfSignature::deserialize_compressed(signature)
+    .map_err(|_| Error::DeserializationError)?;
+
+  let input =
+    vrf_input_point(vrf_input_data).ok_or(Error::VrfInputPointError)?;
+
+  Ok((input, signature))
+}
+
+fn output_hash(output: &Output) -> [u8; 32] {
+  output.hash()[..32]
+    .try_into()
+    .expect("VRF output hash should be 32 bytes")
+}
+
+/// Verifies one Ring VRF signature with a prepared verifier, returning the
+/// VRF output hash.
+fn verify_with(
+  verifier: &RingVerifier,
+  vrf_input_data: &[u8],
+  aux_data: &[u8],
+  signature: &[u8],
+) -> Result<[u8; 32], Error> {
+  let (input, signature) = decode_signature(vrf_input_data, signature)?;
+  verify_decoded(verifier, input, &signature, aux_data)
+}
+
+fn verify_decoded(
+  verifier: &RingVerifier,
+  input: Input,
+  signature: &RingVrfSignature,
+  aux_data: &[u8],
+) -> Result<[u8; 32], Error> {
+  use ark_vrf::ring::Verifier as _;
+
+  if Public::verify(
+    input,
+    signature.output,
+    aux_data,
+    &signature.proof,
+    verifier,
+  )
+  .is_err()
+  {
+    return Err(Error::SignatureVerificationFailed);
+  }
+
+  Ok(output_hash(&signature.output))
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use ark_serialize::CanonicalSerialize;
+
+  use crate::ring_vrf::prover::Prover;
+
+  const RING_SIZE: usize = 6;
+
+  fn secrets() -> Vec<Secret> {
+    (0..RING_SIZE as u8)
+      .map(|i| Secret::from_seed(&[i]))
+      .collect()
+  }
+
+  fn ring(secrets: &[Secret]) -> Vec<Public> {
+    secrets.iter().map(|secret| secret.public()).collect()
+  }
+
+  /// Signs `inputs[i]` with the key at ring position `i`
+  fn sign_all(secrets: &[Secret], inputs: &[Vec<u8>]) -> Vec<Vec<u8>> {
+    inputs
+      .iter()
+      .enumerate()
+      .map(|(i, input)| {
+        Prover::new(ring(secrets), secrets[i].clone(), i)
+          .ring_vrf_sign(input, b"aux")
+          .unwrap()
+      })
+      .collect()
+  }
+
+  fn items<'a>(
+    inputs: &'a [Vec<u8>],
+    signatures: &'a [Vec<u8>],
+  ) -> Vec<(&'a [u8], &'a [u8], &'a [u8])> {
+    inputs
+      .iter()
+      .zip(signatures)
+      .map(|(input, signature)| {
+        (input.as_slice(), &b"aux"[..], signature.as_slice())
+      })
+      .collect()
+  }
+
+  #[test]
+  fn test_verify_batch_valid() {
+    let secrets = secrets();
+    let verifier =
+      Commitment::new(ring_commitment(&ring(&secrets)).unwrap(), RING_SIZE);
+    let inputs: Vec<Vec<u8>> =
+      (0..3).map(|i| format!("ticket {i}").into_bytes()).collect();
+    let signatures = sign_all(&secrets, &inputs);
+
+    let outputs = verifier
+      .ring_vrf_verify_batch(&items(&inputs, &signatures))
+      .unwrap();
+
+    assert_eq!(outputs.len(), 3);
+    for ((input, signature), output) in
+      inputs.iter().zip(&signatures).zip(&outputs)
+    {
+      let single = verifier.ring_vrf_verify(input, b"aux", signature).unwrap();
+      assert_eq!(*output, Some(single));
+    }
+  }
+
+  #[test]
+  fn test_verify_batch_reports_every_failed_entry() {
+    let secrets = secrets();
+    let verifier =
+      Commitment::new(ring_commitment(&ring(&secrets)).unwrap(), RING_SIZE);
+    let inputs: Vec<Vec<u8>> =
+      (0..4).map(|i| format!("ticket {i}").into_bytes()).collect();
+    let mut signatures = sign_all(&secrets, &inputs);
+    // Entry 1 no longer decodes; entry 3 decodes but is bound to other aux
+    // data, so only the proof check itself can reject it
+    let last = signatures[1].len() - 1;
+    signatures[1][last] ^= 1;
+    signatures[3] = Prover::new(ring(&secrets), secrets[3].clone(), 3)
+      .ring_vrf_sign(&inputs[3], b"other aux")
+      .unwrap();
+
+    let outputs = verifier
+      .ring_vrf_verify_batch(&items(&inputs, &signatures))
+      .unwrap();
+
+    let failed: Vec<usize> = (0..4).filter(|&i| outputs[i].is_none()).collect();
+    assert_eq!(failed, [1, 3]);
+    for i in [0, 2] {
+      let single = verifier
+        .ring_vrf_verify(&inputs[i], b"aux", &signatures[i])
+        .unwrap();
+      assert_eq!(outputs[i], Some(single));
+    }
+  }
+
+  #[test]
+  fn test_verify_batch_empty() {
+    let secrets = secrets();
+    let verifier =
+      Commitment::new(ring_commitment(&ring(&secrets)).unwrap(), RING_SIZE);
+
+    assert!(verifier.ring_vrf_verify_batch(&[]).unwrap().is_empty());
+  }
+
+  #[test]
+  fn test_offender_replaced_by_padding_point() {
+    let ring = ring(&secrets());
+    let validators: Vec<[u8; VALIDATOR_DATA_LENGTH]> = ring
+      .iter()
+      .enumerate()
+      .map(|(i, public)| {
+        let mut validator = [0u8; VALIDATOR_DATA_LENGTH];
+        public
+          .serialize_compressed(&mut validator[..PUBLIC_KEY_SIZE])
+          .unwrap();
+        validator[PUBLIC_KEY_SIZE..2 * PUBLIC_KEY_SIZE].fill(i as u8 + 1);
+        validator
+      })
+      .collect();
+    // The second offender is not among the pending validators
+    let offenders = [[4u8; 32], [0xaa; 32]];
+
+    let (commitment, replaced) =
+      ring_commitment_with_offenders(&validators, &offenders).unwrap();
+
+    let mut expected_ring = ring.clone();
+    expected_ring[3] = Public::from_affine(RingProofParams::padding_point());
+    let expected = ring_commitment(&expected_ring).unwrap();
+    assert_eq!(replaced, [3]);
+    assert_eq!(commitment, expected);
+    assert_ne!(commitment, ring_commitment(&ring).unwrap());
+  }
+
+  #[test]
+  fn test_out_of_range_offender_index_rejected() {
+    let mut keys = Vec::new();
+    for public in ring(&secrets()) {
+      public.serialize_compressed(&mut keys).unwrap();
+    }
+
+    let result = ring_commitment_nulling(&keys, &[1, RING_SIZE]);
+
+    assert!(matches!(result, Err(Error::InvalidRingIndex(RING_SIZE))));
+  }
+}
@@ -1,11 +1,13 @@
-use ark_serialize::CanonicalDeserialize;
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
 use ark_vrf::suites::bandersnatch::*;
 
 use lru::LruCache;
-use std::sync::OnceLock;
+use std::sync::{Arc, OnceLock};
 use std::{num::NonZeroUsize, sync::Mutex};
 use thiserror::Error;
 
+use crate::ring_vrf::types::RingCommitment;
+
 // Include the binary data directly in the compiled binary
 static ZCASH_SRS: &[u8] =
   include_bytes!("../../data/zcash-srs-2-11-uncompressed.bin");
@@ -15,6 +17,13 @@ static RING_CONTEXT_CACHE: OnceLock<Mutex<LruCache<usize, RingProofParams>>> =
   OnceLock::new();
 const RING_CONTEXT_CACHE_CAPACITY: usize = 10;
 
+/// Verifiers keyed by ring size and serialized commitment. gamma_z only
+/// changes once per epoch, so a handful of entries covers the current and
+/// next epoch across forks.
+type RingVerifierCache = Mutex<LruCache<(usize, Vec<u8>), Arc<RingVerifier>>>;
+static RING_VERIFIER_CACHE: OnceLock<RingVerifierCache> = OnceLock::new();
+const DEFAULT_RING_VERIFIER_CACHE_CAPACITY: usize = 4;
+
 #[derive(Error, Debug)]
 pub enum RingContextError {
   #[error("Failed to create SRS")]
@@ -55,3 +64,138 @@ pub fn ring_context(
     Ok(ctx)
   }
 }
+
+fn ring_verifier_cache() -> &'static RingVerifierCache {
+  RING_VERIFIER_CACHE.get_or_init(|| {
+    Mutex::new(LruCache::new(
+      NonZeroUsize::new(DEFAULT_RING_VERIFIER_CACHE_CAPACITY)
+        .expect("DEFAULT_RING_VERIFIER_CACHE_CAPACITY must be non-zero"),
+    ))
+  })
+}
+
+/// Creates or retrieves a cached RingVerifier for a ring commitment.
+///
+/// Building a verifier from a commitment (verifier key plus verifier setup)
+/// costs far more than checking a single proof, so verifiers are kept in an
+/// LRU cache keyed by ring size and commitment bytes.
+pub fn ring_verifier(
+  ring_size: usize,
+  commitment: &RingCommitment,
+) -> Result<Arc<RingVerifier>, RingContextError> {
+  let mut commitment_bytes = Vec::new();
+  commitment
+    .serialize_compressed(&mut commitment_bytes)
+    .expect("commitment serializes into a Vec");
+  let key = (ring_size, commitment_bytes);
+
+  let cache = ring_verifier_cache();
+  if let Some(verifier) = cache
+    .lock()
+    .map_err(|_| RingContextError::CacheLockError)?
+    .get(&key)
+  {
+    return Ok(Arc::clone(verifier));
+  }
+
+  // Built outside the lock so that a cache miss does not stall verification
+  // against other commitments
+  let ring_ctx = ring_context(ring_size)?;
+  let verifier_key = ring_ctx.verifier_key_from_commitment(commitment.clone());
+  let verifier = Arc::new(ring_ctx.verifier(verifier_key));
+
+  cache
+    .lock()
+    .map_err(|_| RingContextError::CacheLockError)?
+    .put(key, Arc::clone(&verifier));
+  Ok(verifier)
+}
+
+/// Creates a batch verifier for proofs against a ring commitment.
+///
+/// The batch verifier takes ownership of its ring verifier, so one is built
+/// here from the cached ring context instead of being shared through the
+/// verifier cache.
+pub fn ring_batch_verifier(
+  ring_size: usize,
+  commitment: &RingCommitment,
+) -> Result<RingBatchVerifier, RingContextError> {
+  let ring_ctx = ring_context(ring_size)?;
+  let verifier_key = ring_ctx.verifier_key_from_commitment(commitment.clone());
+  Ok(RingBatchVerifier::new(ring_ctx.verifier(verifier_key)))
+}
+
+/// Sets how many commitments keep a prepared verifier cached, evicting the
+/// least recently used ones if the cache shrinks.
+pub fn set_ring_verifier_cache_capacity(
+  capacity: NonZeroUsize,
+) -> Result<(), RingContextError> {
+  ring_verifier_cache()
+    .lock()
+    .map_err(|_| RingContextError::CacheLockError)?
+    .resize(capacity);
+  Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::ring_vrf::commitment::{ring_commitment, Commitment};
+  use crate::ring_vrf::prover::Prover;
+
+  const RING_SIZE: usize = 6;
+
+  // The verifier cache is process wide; keep these tests from resizing it
+  // under each other
+  static CACHE_TEST_LOCK: Mutex<()> = Mutex::new(());
+
+  fn secrets(seed: u8) -> Vec<Secret> {
+    (0..RING_SIZE as u8)
+      .map(|i| Secret::from_seed(&[seed, i]))
+      .collect()
+  }
+
+  fn commitment(secrets: &[Secret]) -> RingCommitment {
+    let ring: Vec<Public> = secrets.iter().map(|s| s.public()).collect();
+    ring_commitment(&ring).unwrap()
+  }
+
+  #[test]
+  fn test_cache_hit_returns_same_verifier() {
+    let _guard = CACHE_TEST_LOCK.lock().unwrap();
+    let commitment = commitment(&secrets(1));
+
+    let first = ring_verifier(RING_SIZE, &commitment).unwrap();
+    let second = ring_verifier(RING_SIZE, &commitment).unwrap();
+
+    assert!(Arc::ptr_eq(&first, &second));
+  }
+
+  #[test]
+  fn test_capacity_one_evicts_previous_verifier() {
+    let _guard = CACHE_TEST_LOCK.lock().unwrap();
+    let secrets_a = secrets(2);
+    let commitment_a = commitment(&secrets_a);
+    let commitment_b = commitment(&secrets(3));
+
+    set_ring_verifier_cache_capacity(NonZeroUsize::new(1).unwrap()).unwrap();
+    let first = ring_verifier(RING_SIZE, &commitment_a).unwrap();
+    ring_verifier(RING_SIZE, &commitment_b).unwrap();
+    let rebuilt = ring_verifier(RING_SIZE, &commitment_a).unwrap();
+    assert!(!Arc::ptr_eq(&first, &rebuilt));
+
+    // The rebuilt verifier still accepts a signature from the ring
+    let ring: Vec<Public> = secrets_a.iter().map(|s| s.public()).collect();
+    let signature = Prover::new(ring, secrets_a[4].clone(), 4)
+      .ring_vrf_sign(b"ticket", b"aux")
+      .unwrap();
+    let verified = Commitment::new(commitment_a, RING_SIZE)
+      .ring_vrf_verify(b"ticket", b"aux", &signature);
+
+    set_ring_verifier_cache_capacity(
+      NonZeroUsize::new(DEFAULT_RING_VERIFIER_CACHE_CAPACITY).unwrap(),
+    )
+    .unwrap();
+    assert!(verified.is_ok());
+  }
+}
@@ -1,8 +1,12 @@
-use super::commitment::Commitment;
-use super::context::ring_context;
+use super::commitment::{
+  ring_commitment, ring_commitment_with_offenders, Commitment,
+};
+use super::context::{ring_context, set_ring_verifier_cache_capacity};
 use super::prover::Prover;
 use super::types::*;
 use super::verifier::Verifier;
+use crate::sign::BandersnatchSecret;
+use crate::validator_keys::VALIDATOR_DATA_LENGTH;
 use ark_serialize::CanonicalDeserialize;
 use ark_serialize::CanonicalSerialize;
 use libc::size_t;
@@ -15,7 +19,7 @@ use ark_vrf::suites::bandersnatch::Secret;
 ///
 /// The ring size is determined by the number of public keys passed (public_keys_len / PUBLIC_KEY_SIZE).
 /// If any public key in the array is invalid or zeroed out, it will be replaced with a padding point
-/// in the ring.
+/// in the ring; ring_vrf_verifier_padding reports which positions were replaced and why.
 ///
 /// # Safety
 /// - `public_keys` must point to a contiguous array of serialized public keys
@@ -37,32 +41,20 @@ pub unsafe extern "C" fn new_ring_vrf_verifier(
     std::slice::from_raw_parts(public_keys, public_keys_len);
   let num_keys = public_keys_len / PUBLIC_KEY_SIZE;
 
-  let padding_point = match ring_context(num_keys) {
-    Ok(_) => ark_vrf::ring::RingProofParams::<
-      ark_vrf::suites::bandersnatch::BandersnatchSha512Ell2,
-    >::padding_point(),
+  let (ring, padded) = match ring_with_padding(public_keys_slice) {
+    Ok(decoded) => decoded,
     Err(_) => return std::ptr::null_mut(),
   };
-  // Using deserialize_compressed_unchecked instead of deserialize_compressed
-  // to accept any valid point on the Bandersnatch curve, not just those in the prime subgroup.
-  // This matches davxy and Parity's implementation and is safe because:
-  // 1. Invalid keys should never reach JAM (filtered by PoP verification beforehand)
-  // 2. It's faster and prevents replacement with padding points that would alter VRF outputs
-  // See: JAM conformance test 1754990132 gamma.z difference
-  let ring: Vec<Public> = public_keys_slice
-    .chunks(PUBLIC_KEY_SIZE)
-    .map(|chunk| {
-      Public::deserialize_compressed_unchecked(chunk)
-        .unwrap_or(Public::from(padding_point))
-    })
-    .collect();
 
   if ring.len() != num_keys {
     return std::ptr::null_mut();
   }
 
   match Verifier::new(ring) {
-    Ok(verifier) => Box::into_raw(Box::new(verifier)),
+    Ok(mut verifier) => {
+      verifier.padded = padded;
+      Box::into_raw(Box::new(verifier))
+    }
     Err(_) => std::ptr::null_mut(),
   }
 }
@@ -79,6 +71,9 @@ pub unsafe extern "C" fn free_ring_vrf_verifier(verifier: *mut Verifier) {
 
 /// Create a new Ring VRF Prover.
 ///
+/// Keys that fail to decode are replaced with the padding point as in
+/// new_ring_vrf_verifier; see ring_vrf_prover_padding.
+///
 /// # Safety
 /// - All pointers must be valid and point to sufficient memory
 #[no_mangle]
@@ -108,27 +103,112 @@ pub unsafe extern "C" fn new_ring_vrf_prover(
     return std::ptr::null_mut();
   };
 
-  let padding_point = match ring_context(public_keys_len / PUBLIC_KEY_SIZE) {
-    Ok(_) => ark_vrf::ring::RingProofParams::<
-      ark_vrf::suites::bandersnatch::BandersnatchSha512Ell2,
-    >::padding_point(),
+  let (ring, padded) = match ring_with_padding(public_keys_slice) {
+    Ok(decoded) => decoded,
     Err(_) => return std::ptr::null_mut(),
   };
-  // Using deserialize_compressed_unchecked instead of deserialize_compressed
-  // to accept any valid point on the Bandersnatch curve, not just those in the prime subgroup.
-  // This matches davxy and Parity's implementation and is safe because:
-  // 1. Invalid keys should never reach JAM (filtered by PoP verification beforehand)
-  // 2. It's faster and prevents replacement with padding points that would alter VRF outputs
-  // See: JAM conformance test 1754990132 gamma.z difference
-  let ring: Vec<Public> = public_keys_slice
-    .chunks(PUBLIC_KEY_SIZE)
-    .map(|chunk| {
-      Public::deserialize_compressed_unchecked(chunk)
-        .unwrap_or(Public::from(padding_point))
-    })
-    .collect();
 
-  Box::into_raw(Box::new(Prover::new(ring, secret, prover_idx)))
+  let mut prover = Prover::new(ring, secret, prover_idx);
+  prover.padded = padded;
+  Box::into_raw(Box::new(prover))
+}
+
+/// Create a new Ring VRF Prover from a Bandersnatch secret handle.
+///
+/// Same as new_ring_vrf_prover, but the secret stays inside Rust. The prover
+/// keeps its own copy of the secret, zeroized when the prover is freed, so the
+/// handle may be freed independently.
+///
+/// # Safety
+/// - `secret` must be a valid pointer returned by bandersnatch_secret_from_seed
+/// - `public_keys` must point to a contiguous array of serialized public keys
+#[no_mangle]
+pub unsafe extern "C" fn new_ring_vrf_prover_with_secret(
+  secret: *const BandersnatchSecret,
+  public_keys: *const u8,
+  public_keys_len: size_t,
+  prover_idx: size_t,
+) -> *mut Prover {
+  debug_assert!(!secret.is_null(), "secret pointer must not be null");
+  debug_assert!(
+    !public_keys.is_null(),
+    "public_keys pointer must not be null"
+  );
+  debug_assert!(
+    public_keys_len.is_multiple_of(PUBLIC_KEY_SIZE),
+    "public_keys_len must be a multiple of PUBLIC_KEY_SIZE"
+  );
+
+  let public_keys_slice =
+    std::slice::from_raw_parts(public_keys, public_keys_len);
+
+  let (ring, padded) = match ring_with_padding(public_keys_slice) {
+    Ok(decoded) => decoded,
+    Err(_) => return std::ptr::null_mut(),
+  };
+
+  let secret = (*secret).0.clone();
+  let mut prover = Prover::new(ring, secret, prover_idx);
+  prover.padded = padded;
+  Box::into_raw(Box::new(prover))
+}
+
+/// Writes one reason code per ring position: 0 if the given key is used,
+/// otherwise the `PaddingReason` it was replaced for.
+unsafe fn write_padding(
+  padded: &[PaddedKey],
+  ring_size: usize,
+  reasons_out: *mut u8,
+) -> size_t {
+  if !reasons_out.is_null() {
+    let reasons = std::slice::from_raw_parts_mut(reasons_out, ring_size);
+    reasons.fill(0);
+    for key in padded {
+      reasons[key.index] = key.reason as u8;
+    }
+  }
+  padded.len()
+}
+
+/// Report which ring positions of a verifier hold the padding point.
+///
+/// new_ring_vrf_verifier replaces keys that fail to decode with the padding
+/// point, which changes the ring commitment. When `reasons_out` is non-null,
+/// entry `i` is set to 0 if key `i` was used as given, 1 if it was replaced
+/// as an all-zero (null) key and 2 if it was not a valid point encoding.
+///
+/// Returns the number of padded positions.
+///
+/// # Safety
+/// - `verifier` must be a valid pointer returned by new_ring_vrf_verifier
+/// - `reasons_out` must be null or point to ring size writable bytes
+#[no_mangle]
+pub unsafe extern "C" fn ring_vrf_verifier_padding(
+  verifier: *const Verifier,
+  reasons_out: *mut u8,
+) -> size_t {
+  debug_assert!(!verifier.is_null(), "verifier pointer must not be null");
+  let verifier = &*verifier;
+  write_padding(&verifier.padded, verifier.ring.len(), reasons_out)
+}
+
+/// Report which ring positions of a prover hold the padding point.
+///
+/// Same as ring_vrf_verifier_padding, for handles returned by
+/// new_ring_vrf_prover and new_ring_vrf_prover_with_secret.
+///
+/// # Safety
+/// - `prover` must be a valid pointer returned by new_ring_vrf_prover or
+///   new_ring_vrf_prover_with_secret
+/// - `reasons_out` must be null or point to ring size writable bytes
+#[no_mangle]
+pub unsafe extern "C" fn ring_vrf_prover_padding(
+  prover: *const Prover,
+  reasons_out: *mut u8,
+) -> size_t {
+  debug_assert!(!prover.is_null(), "prover pointer must not be null");
+  let prover = &*prover;
+  write_padding(&prover.padded, prover.ring.len(), reasons_out)
 }
 
 /// Free a Ring VRF Prover.
@@ -310,6 +390,135 @@ pub unsafe extern "C" fn vrf_get_commitment(
   true
 }
 
+/// Compute the ring commitment (gamma_z) of a set of public keys.
+///
+/// Equivalent to new_ring_vrf_verifier followed by vrf_get_commitment,
+/// including the replacement of invalid or zeroed keys with the padding point,
+/// but without allocating a verifier.
+///
+/// # Safety
+/// - `public_keys` must point to a contiguous array of serialized public keys
+/// - `commitment_out` must point to 144 writable bytes
+#[no_mangle]
+pub unsafe extern "C" fn ring_commitment_from_keys(
+  public_keys: *const u8,
+  public_keys_len: size_t,
+  commitment_out: *mut u8,
+) -> bool {
+  debug_assert!(
+    !public_keys.is_null(),
+    "public_keys pointer must not be null"
+  );
+  debug_assert!(
+    !commitment_out.is_null(),
+    "commitment_out pointer must not be null"
+  );
+  if !public_keys_len.is_multiple_of(PUBLIC_KEY_SIZE) {
+    return false;
+  }
+
+  let public_keys_slice =
+    std::slice::from_raw_parts(public_keys, public_keys_len);
+  let Ok(ring) = ring_from_public_keys(public_keys_slice) else {
+    return false;
+  };
+  let Ok(commitment) = ring_commitment(&ring) else {
+    return false;
+  };
+
+  let mut commitment_bytes = Vec::new();
+  if commitment
+    .serialize_compressed(&mut commitment_bytes)
+    .is_err()
+  {
+    return false;
+  }
+
+  std::ptr::copy_nonoverlapping(commitment_bytes.as_ptr(), commitment_out, 144);
+  true
+}
+
+/// Compute gamma_z for a new epoch, replacing the keys of offenders by the
+/// null key first (Gray Paper eq. 6.14).
+///
+/// # Arguments
+/// * `validator_data` - `validator_count` concatenated 336-byte
+///   `ValidatorData` entries (the pending keys)
+/// * `offenders` - `offenders_count` concatenated 32-byte Ed25519 keys; may be
+///   null if `offenders_count` is 0
+/// * `commitment_out` - Receives the 144-byte ring commitment
+/// * `replaced_out` - Optional array of `validator_count` bytes; when
+///   non-null, entry `i` is set to 1 if validator `i` was nulled as an
+///   offender and 0 otherwise
+///
+/// Returns false if no ring context exists for `validator_count`.
+///
+/// # Safety
+/// - All pointers must be valid for the sizes given above
+#[no_mangle]
+pub unsafe extern "C" fn ring_commitment_from_validators(
+  validator_data: *const u8,
+  validator_count: size_t,
+  offenders: *const u8,
+  offenders_count: size_t,
+  commitment_out: *mut u8,
+  replaced_out: *mut u8,
+) -> bool {
+  debug_assert!(
+    !validator_data.is_null(),
+    "validator_data pointer must not be null"
+  );
+  debug_assert!(
+    !offenders.is_null() || offenders_count == 0,
+    "offenders pointer must not be null"
+  );
+  debug_assert!(
+    !commitment_out.is_null(),
+    "commitment_out pointer must not be null"
+  );
+
+  let validators: Vec<[u8; VALIDATOR_DATA_LENGTH]> =
+    std::slice::from_raw_parts(
+      validator_data,
+      validator_count * VALIDATOR_DATA_LENGTH,
+    )
+    .chunks_exact(VALIDATOR_DATA_LENGTH)
+    .map(|chunk| chunk.try_into().expect("chunk has validator data length"))
+    .collect();
+  let offenders: Vec<[u8; 32]> = if offenders_count == 0 {
+    Vec::new()
+  } else {
+    std::slice::from_raw_parts(offenders, offenders_count * 32)
+      .chunks_exact(32)
+      .map(|chunk| chunk.try_into().expect("chunk has ed25519 key length"))
+      .collect()
+  };
+
+  let Ok((commitment, replaced)) =
+    ring_commitment_with_offenders(&validators, &offenders)
+  else {
+    return false;
+  };
+
+  let mut commitment_bytes = Vec::new();
+  if commitment
+    .serialize_compressed(&mut commitment_bytes)
+    .is_err()
+  {
+    return false;
+  }
+  std::ptr::copy_nonoverlapping(commitment_bytes.as_ptr(), commitment_out, 144);
+
+  if !replaced_out.is_null() {
+    let flags = std::slice::from_raw_parts_mut(replaced_out, validator_count);
+    flags.fill(0);
+    for i in replaced {
+      flags[i] = 1;
+    }
+  }
+  true
+}
+
 /// Verify against commitment
 
 /// # Safety
@@ -353,6 +562,110 @@ pub unsafe extern "C" fn vrf_verify_ring_signature_against_commitment(
   }
 }
 
+/// Verify a batch of ring signatures against one commitment.
+///
+/// Intended for the tickets extrinsic, whose proofs are all checked against
+/// the same gamma_z. The proofs are verified together in one batch; only if
+/// the batch fails is each proof checked on its own to find the bad ones.
+///
+/// Writes the 32-byte VRF output hash of signature `i` to
+/// `vrf_outputs[i * 32..]`, zeroed for signatures that fail. When `results` is
+/// non-null, entry `i` is set to `1` if signature `i` is valid and `0`
+/// otherwise. Returns true only if every signature is valid; `count == 0`
+/// succeeds without touching any pointer. If the commitment cannot be used,
+/// every entry is reported as failed. A null argument pointer returns false
+/// without writing anything; a null data pointer fails only its own entry.
+///
+/// # Safety
+/// - `commitment` must point to 144 bytes
+/// - `vrf_inputs`, `vrf_input_lens`, `aux_data` and `aux_data_lens` must be
+///   arrays of `count` entries, each data pointer valid for its length
+/// - `signatures` must point to `count` concatenated 784-byte signatures
+/// - `vrf_outputs` must point to `count * 32` writable bytes
+/// - `results`, if non-null, must point to `count` writable bytes
+#[no_mangle]
+pub unsafe extern "C" fn vrf_verify_ring_signatures_against_commitment(
+  commitment: *const u8,
+  ring_size: size_t,
+  vrf_inputs: *const *const u8,
+  vrf_input_lens: *const size_t,
+  aux_data: *const *const u8,
+  aux_data_lens: *const size_t,
+  signatures: *const u8,
+  count: size_t,
+  vrf_outputs: *mut u8,
+  results: *mut u8,
+) -> bool {
+  if count == 0 {
+    return true;
+  }
+  if commitment.is_null()
+    || vrf_inputs.is_null()
+    || vrf_input_lens.is_null()
+    || aux_data.is_null()
+    || aux_data_lens.is_null()
+    || signatures.is_null()
+    || vrf_outputs.is_null()
+  {
+    return false;
+  }
+
+  let input_ptrs = std::slice::from_raw_parts(vrf_inputs, count);
+  let input_lens = std::slice::from_raw_parts(vrf_input_lens, count);
+  let aux_ptrs = std::slice::from_raw_parts(aux_data, count);
+  let aux_lens = std::slice::from_raw_parts(aux_data_lens, count);
+  let sigs = std::slice::from_raw_parts(signatures, count * 784);
+
+  let items: Vec<(&[u8], &[u8], &[u8])> = (0..count)
+    .map(|i| {
+      if input_ptrs[i].is_null() || aux_ptrs[i].is_null() {
+        // An empty signature never decodes, so the entry fails on its own
+        return (&[][..], &[][..], &[][..]);
+      }
+      (
+        std::slice::from_raw_parts(input_ptrs[i], input_lens[i]),
+        std::slice::from_raw_parts(aux_ptrs[i], aux_lens[i]),
+        &sigs[i * 784..(i + 1) * 784],
+      )
+    })
+    .collect();
+
+  let commitment_slice = std::slice::from_raw_parts(commitment, 144);
+  let outputs = RingCommitment::deserialize_compressed(commitment_slice)
+    .ok()
+    .and_then(|commitment| {
+      Commitment::new(commitment, ring_size)
+        .ring_vrf_verify_batch(&items)
+        .ok()
+    })
+    .unwrap_or_else(|| vec![None; count]);
+
+  let out = std::slice::from_raw_parts_mut(vrf_outputs, count * 32);
+  for (chunk, output) in out.chunks_exact_mut(32).zip(&outputs) {
+    chunk.copy_from_slice(&output.unwrap_or_default());
+  }
+  if !results.is_null() {
+    let results = std::slice::from_raw_parts_mut(results, count);
+    for (result, output) in results.iter_mut().zip(&outputs) {
+      *result = output.is_some() as u8;
+    }
+  }
+
+  outputs.iter().all(Option::is_some)
+}
+
+/// Set the capacity of the prepared ring verifier cache.
+///
+/// The commitment-based verification calls keep a verifier per commitment
+/// (i.e. per gamma_z) in an LRU cache. Returns false if `capacity` is 0.
+#[no_mangle]
+pub extern "C" fn ring_verifier_cache_set_capacity(capacity: size_t) -> bool {
+  match std::num::NonZeroUsize::new(capacity) {
+    Some(capacity) => set_ring_verifier_cache_capacity(capacity).is_ok(),
+    None => false,
+  }
+}
+
 /// IETF VRF Sign (non-anonymous).
 ///
 /// Creates a deterministic VRF signature from the Prover's secret key on the given input data.
@@ -465,7 +778,7 @@ pub unsafe extern "C" fn get_padding_point(
   output: *mut u8,
 ) -> bool {
   let padding_point = match ring_context(ring_size) {
-    Ok(_) => Public::from(ark_vrf::ring::RingProofParams::<
+    Ok(_) => Public::from_affine(ark_vrf::ring::RingProofParams::<
       ark_vrf::suites::bandersnatch::BandersnatchSha512Ell2,
     >::padding_point()),
     Err(_) => return false,
@@ -481,3 +794,96 @@ pub unsafe extern "C" fn get_padding_point(
 
   true
 }
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn public_keys(ring_size: u8) -> Vec<u8> {
+    let mut keys = Vec::new();
+    for i in 0..ring_size {
+      Secret::from_seed(&[i])
+        .public()
+        .serialize_compressed(&mut keys)
+        .unwrap();
+    }
+    keys
+  }
+
+  #[test]
+  fn test_commitment_from_keys_matches_verifier() {
+    let mut keys = public_keys(6);
+    // A nulled key is padded the same way on both paths
+    keys[2 * PUBLIC_KEY_SIZE..3 * PUBLIC_KEY_SIZE].fill(0);
+
+    let mut from_keys = [0u8; 144];
+    let mut from_verifier = [0u8; 144];
+    unsafe {
+      assert!(ring_commitment_from_keys(
+        keys.as_ptr(),
+        keys.len(),
+        from_keys.as_mut_ptr(),
+      ));
+
+      let verifier = new_ring_vrf_verifier(keys.as_ptr(), keys.len());
+      assert!(!verifier.is_null());
+      assert!(vrf_get_commitment(verifier, from_verifier.as_mut_ptr()));
+      free_ring_vrf_verifier(verifier);
+    }
+
+    assert_eq!(from_keys, from_verifier);
+  }
+
+  #[test]
+  fn test_padding_reports_exactly_the_replaced_keys() {
+    let mut keys = public_keys(6);
+    // Key 1 is not a point encoding, key 4 is the null key of an offender
+    keys[PUBLIC_KEY_SIZE..2 * PUBLIC_KEY_SIZE].fill(0xff);
+    keys[4 * PUBLIC_KEY_SIZE..5 * PUBLIC_KEY_SIZE].fill(0);
+    let expected_reasons = [0, 2, 0, 0, 1, 0];
+
+    let (ring, padded) = ring_with_padding(&keys).unwrap();
+    assert_eq!(
+      padded,
+      [
+        PaddedKey {
+          index: 1,
+          reason: PaddingReason::InvalidEncoding,
+        },
+        PaddedKey {
+          index: 4,
+          reason: PaddingReason::NullKey,
+        },
+      ]
+    );
+    let padding_point = Public::from_affine(ark_vrf::ring::RingProofParams::<
+      ark_vrf::suites::bandersnatch::BandersnatchSha512Ell2,
+    >::padding_point());
+    for (i, public) in ring.iter().enumerate() {
+      assert_eq!(*public == padding_point, i == 1 || i == 4);
+    }
+
+    let mut secret = Vec::new();
+    Secret::from_seed(&[0])
+      .serialize_compressed(&mut secret)
+      .unwrap();
+
+    unsafe {
+      let verifier = new_ring_vrf_verifier(keys.as_ptr(), keys.len());
+      assert!(!verifier.is_null());
+      let mut reasons = [0xee; 6];
+      assert_eq!(ring_vrf_verifier_padding(verifier, reasons.as_mut_ptr()), 2);
+      assert_eq!(reasons, expected_reasons);
+      assert_eq!(ring_vrf_verifier_padding(verifier, ptr::null_mut()), 2);
+      free_ring_vrf_verifier(verifier);
+
+      let prover =
+        new_ring_vrf_prover(secret.as_ptr(), keys.as_ptr(), keys.len(), 0);
+      assert!(!prover.is_null());
+      let mut reasons = [0xee; 6];
+      assert_eq!(ring_vrf_prover_padding(prover, reasons.as_mut_ptr()), 2);
+      assert_eq!(reasons, expected_reasons);
+      free_ring_vrf_prover(prover);
+    }
+  }
+}
@@ -4,7 +4,7 @@ use thiserror::Error;
 
 use crate::ring_vrf::{
   context::{ring_context, RingContextError},
-  types::{vrf_input_point, IetfVrfSignature, RingVrfSignature},
+  types::{vrf_input_point, IetfVrfSignature, PaddedKey, RingVrfSignature},
 };
 
 #[derive(Error, Debug)]
@@ -29,6 +29,8 @@ pub struct Prover {
   pub ring: Vec<Public>,
   /// Position of the corresponding Prover's public key in the ring
   pub prover_idx: usize,
+  /// Ring positions whose given key was replaced by the padding point
+  pub padded: Vec<PaddedKey>,
 }
 
 impl Prover {
@@ -50,6 +52,7 @@ impl Prover {
       prover_idx,
       secret: prover_secret,
       ring,
+      padded: Vec::new(),
     }
   }
 
@@ -2,6 +2,8 @@ use ark_serialize::CanonicalSerialize;
 use ark_vrf::reexports::ark_serialize::CanonicalDeserialize;
 use ark_vrf::suites::bandersnatch::*;
 
+use crate::ring_vrf::context::{ring_context, RingContextError};
+
 pub const DEFAULT_RING_SIZE: usize = 1023;
 pub const SECRET_KEY_SIZE: usize = 32;
 pub const PUBLIC_KEY_SIZE: usize = 32;
@@ -11,6 +13,24 @@ pub fn vrf_input_point(vrf_input_data: &[u8]) -> Option<Input> {
   Input::new(vrf_input_data)
 }
 
+/// Why a ring position holds the padding point instead of the given key.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(u8)]
+pub enum PaddingReason {
+  /// All-zero key, e.g. the null key of an offender
+  NullKey = 1,
+  /// Not a valid compressed Bandersnatch point
+  InvalidEncoding = 2,
+}
+
+/// A ring position whose key was replaced by the padding point.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PaddedKey {
+  /// Position in the ring
+  pub index: usize,
+  pub reason: PaddingReason,
+}
+
 pub type RingCommitment = ark_vrf::ring::RingCommitment<BandersnatchSha512Ell2>;
 
 /// Represents the output of the standard (non-anonymous) IETF VRF `Prove` operation. This
@@ -33,3 +53,46 @@ pub struct RingVrfSignature {
   /// This contains both the Pedersen proof and actual ring proof.
   pub proof: RingProof,
 }
+
+/// Deserializes a ring of public keys, replacing undecodable keys with the
+/// padding point.
+///
+/// Returns the ring and the positions that were padded, or an error if no
+/// ring context exists for the ring size.
+pub fn ring_with_padding(
+  public_keys: &[u8],
+) -> Result<(Vec<Public>, Vec<PaddedKey>), RingContextError> {
+  ring_context(public_keys.len() / PUBLIC_KEY_SIZE)?;
+  let padding_point = Public::from_affine(RingProofParams::padding_point());
+  // Using deserialize_compressed_unchecked instead of deserialize_compressed
+  // to accept any valid point on the Bandersnatch curve, not just those in the prime subgroup.
+  // This matches davxy and Parity's implementation and is safe because:
+  // 1. Invalid keys should never reach JAM (filtered by PoP verification beforehand)
+  // 2. It's faster and prevents replacement with padding points that would alter VRF outputs
+  // See: JAM conformance test 1754990132 gamma.z difference
+  let mut padded = Vec::new();
+  let ring = public_keys
+    .chunks(PUBLIC_KEY_SIZE)
+    .enumerate()
+    .map(|(index, chunk)| {
+      Public::deserialize_compressed_unchecked(chunk).unwrap_or_else(|_| {
+        let reason = if chunk.iter().all(|&b| b == 0) {
+          PaddingReason::NullKey
+        } else {
+          PaddingReason::InvalidEncoding
+        };
+        padded.push(PaddedKey { index, reason });
+        padding_point
+      })
+    })
+    .collect();
+
+  Ok((ring, padded))
+}
+
+/// Same as `ring_with_padding`, without the padding report.
+pub fn ring_from_public_keys(
+  public_keys: &[u8],
+) -> Result<Vec<Public>, RingContextError> {
+  ring_with_padding(public_keys).map(|(ring, _)| ring)
+}
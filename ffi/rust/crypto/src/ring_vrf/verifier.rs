@@ -3,9 +3,11 @@ use ark_vrf::suites::bandersnatch::*;
 use thiserror::Error;
 
 use super::{
+  commitment::ring_commitment,
   context::{ring_context, RingContextError},
   types::{
-    vrf_input_point, IetfVrfSignature, RingCommitment, RingVrfSignature,
+    vrf_input_point, IetfVrfSignature, PaddedKey, RingCommitment,
+    RingVrfSignature,
   },
 };
 
@@ -31,16 +33,19 @@ pub struct Verifier {
   pub commitment: RingCommitment,
   /// Ring of public keys
   pub ring: Vec<Public>,
+  /// Ring positions whose given key was replaced by the padding point
+  pub padded: Vec<PaddedKey>,
 }
 
 impl Verifier {
   /// Creates a new Verifier with the given ring of public keys
   pub fn new(ring: Vec<Public>) -> Result<Self, VerifierError> {
-    // Backend currently requires the wrapped type (plain affine points)
-    let pts: Vec<_> = ring.iter().map(|pk| pk.0).collect();
-    let verifier_key = ring_context(ring.len())?.verifier_key(&pts);
-    let commitment = verifier_key.commitment();
-    Ok(Self { ring, commitment })
+    let commitment = ring_commitment(&ring)?;
+    Ok(Self {
+      ring,
+      commitment,
+      padded: Vec::new(),
+    })
   }
 
   /// Non-Anonymous VRF signature verification.
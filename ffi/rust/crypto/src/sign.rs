@@ -29,8 +29,8 @@ fn create_vrf_input(input_data: &[u8]) -> Input {
 ///
 /// Used for ticket claiming during block production
 /// Only vrf_input_data affects the VRF output according to equation G.2
-fn bandersnatch_sign_impl(
-  secret: Secret,
+pub(crate) fn bandersnatch_sign_impl(
+  secret: &Secret,
   vrf_input_data: &[u8],
   context_data: &[u8],
 ) -> BandersnatchSignature {
@@ -161,7 +161,124 @@ pub unsafe extern "C" fn bandersnatch_sign(
     return -1;
   };
 
-  let signature = bandersnatch_sign_impl(secret, vrf_input, context);
+  let signature = bandersnatch_sign_impl(&secret, vrf_input, context);
+
+  let mut signature_buf = [0u8; SIGNATURE_LENGTH];
+  if signature
+    .serialize_compressed(&mut signature_buf[..])
+    .is_err()
+  {
+    return -1;
+  }
+
+  ptr::copy_nonoverlapping(
+    signature_buf.as_ptr(),
+    signature_out,
+    signature_buf.len(),
+  );
+
+  0
+}
+
+/// Bandersnatch secret key kept on the Rust side of the FFI boundary.
+///
+/// The secret is never written to caller memory. Dropping the handle runs the
+/// ark-vrf `Secret` destructor, which zeroizes the secret scalar.
+pub struct BandersnatchSecret(pub(crate) Secret);
+
+/// Creates a Bandersnatch secret handle from a seed
+///
+/// Derives the same secret as bandersnatch_new_secret, but returns it as an
+/// opaque handle. Returns null on error.
+///
+/// # Safety
+/// - `seed` must point to valid memory of `seed_len` bytes
+#[no_mangle]
+pub unsafe extern "C" fn bandersnatch_secret_from_seed(
+  seed: *const u8,
+  seed_len: size_t,
+) -> *mut BandersnatchSecret {
+  if seed.is_null() {
+    return ptr::null_mut();
+  }
+
+  let seed_slice = slice::from_raw_parts(seed, seed_len);
+  Box::into_raw(Box::new(BandersnatchSecret(Secret::from_seed(seed_slice))))
+}
+
+/// Frees a Bandersnatch secret handle, zeroizing the secret
+///
+/// # Safety
+/// - `secret` must be a valid pointer returned by bandersnatch_secret_from_seed
+#[no_mangle]
+pub unsafe extern "C" fn bandersnatch_free_secret(
+  secret: *mut BandersnatchSecret,
+) {
+  debug_assert!(!secret.is_null(), "secret pointer must not be null");
+  drop(Box::from_raw(secret));
+}
+
+/// Derives the public key of a Bandersnatch secret handle
+///
+/// Writes the public key to public_out which must be BANDERSNATCH_PUBLIC_LENGTH bytes
+/// Returns 0 on success, -1 on error
+///
+/// # Safety
+/// - `secret` must be a valid pointer returned by bandersnatch_secret_from_seed
+#[no_mangle]
+pub unsafe extern "C" fn bandersnatch_secret_public(
+  secret: *const BandersnatchSecret,
+  public_out: *mut u8,
+) -> c_int {
+  if secret.is_null() || public_out.is_null() {
+    return -1;
+  }
+
+  let mut public_buf = [0u8; PUBLIC_LENGTH];
+  if (*secret)
+    .0
+    .public()
+    .serialize_compressed(&mut public_buf[..])
+    .is_err()
+  {
+    return -1;
+  }
+
+  ptr::copy_nonoverlapping(public_buf.as_ptr(), public_out, PUBLIC_LENGTH);
+
+  0
+}
+
+/// Creates a VRF signature according to equation G.1 using a secret handle
+///
+/// Same as bandersnatch_sign, but the secret never crosses the FFI boundary.
+/// Writes signature to signature_out which must be BANDERSNATCH_SIGNATURE_LENGTH bytes
+/// Returns 0 on success, -1 on error
+///
+/// # Safety
+/// - `secret` must be a valid pointer returned by bandersnatch_secret_from_seed
+/// - All other pointers must be valid for their respective lengths
+#[no_mangle]
+pub unsafe extern "C" fn bandersnatch_secret_sign(
+  secret: *const BandersnatchSecret,
+  vrf_input_data: *const u8,
+  vrf_input_len: size_t,
+  context_data: *const u8,
+  context_len: size_t,
+  signature_out: *mut u8,
+) -> c_int {
+  if secret.is_null()
+    || vrf_input_data.is_null()
+    || context_data.is_null()
+    || signature_out.is_null()
+  {
+    return -1;
+  }
+
+  let vrf_input = slice::from_raw_parts(vrf_input_data, vrf_input_len);
+  let context = slice::from_raw_parts(context_data, context_len);
+
+  let signature = bandersnatch_sign_impl(&(*secret).0, vrf_input, context);
 
   let mut signature_buf = [0u8; SIGNATURE_LENGTH];
   if signature
@@ -241,6 +358,141 @@ pub unsafe extern "C" fn bandersnatch_verify(
   }
 }
 
+/// Entry of a batch verification: (public key, vrf input, context, signature)
+pub type VerifyBatchItem<'a> = (
+  [u8; PUBLIC_LENGTH],
+  &'a [u8],
+  &'a [u8],
+  [u8; SIGNATURE_LENGTH],
+);
+
+/// Verifies a batch of entries according to equation G.1
+///
+/// Returns the VRF output hash Y(s) of each entry, or `None` for entries that
+/// fail to decode or verify. The (c, s) proof form cannot be folded into a
+/// single check, so the entries are verified independently, spread over
+/// rayon's thread pool
+pub fn bandersnatch_verify_batch_impl(
+  items: &[VerifyBatchItem],
+) -> Vec<Option<[u8; OUTPUT_LENGTH]>> {
+  use rayon::prelude::*;
+
+  items
+    .par_iter()
+    .map(|(public_key, vrf_input, context, signature)| {
+      let public =
+        Public::deserialize_compressed_unchecked(&public_key[..]).ok()?;
+      let signature =
+        BandersnatchSignature::deserialize_compressed_unchecked(&signature[..])
+          .ok()?;
+      bandersnatch_verify_impl(public, vrf_input, context, signature).ok()
+    })
+    .collect()
+}
+
+/// Views `len` bytes at `ptr`, which may be null only if `len` is 0
+unsafe fn optional_slice<'a>(ptr: *const u8, len: size_t) -> Option<&'a [u8]> {
+  match (ptr.is_null(), len) {
+    (_, 0) => Some(&[]),
+    (true, _) => None,
+    (false, _) => Some(slice::from_raw_parts(ptr, len)),
+  }
+}
+
+/// Verifies `count` VRF signatures according to equation G.1, e.g. the seal
+/// H_s and entropy source H_v of a run of headers
+///
+/// # Arguments
+/// * `public_keys` - `count` concatenated 32-byte public keys
+/// * `vrf_inputs` - Array of `count` vrf input pointers
+/// * `vrf_input_lens` - Array of `count` vrf input lengths
+/// * `contexts` - Array of `count` context pointers
+/// * `context_lens` - Array of `count` context lengths
+/// * `signatures` - `count` concatenated 96-byte signatures
+/// * `count` - Number of entries in the batch
+/// * `output_hashes_out` - `count * 32` bytes receiving the output hash Y(s)
+///   of each entry; entries that fail are zeroed
+/// * `results` - Optional output array of `count` bytes; when non-null, entry
+///   `i` is set to `1` if signature `i` is valid and `0` otherwise
+///
+/// # Returns
+/// * `0` - All signatures are valid
+/// * `-1` - At least one signature is invalid or inputs are malformed
+///
+/// # Safety
+/// Caller must ensure all pointers are valid and point to appropriately sized
+/// buffers. An input or context pointer may only be null if its length is 0.
+#[no_mangle]
+pub unsafe extern "C" fn bandersnatch_verify_batch(
+  public_keys: *const u8,
+  vrf_inputs: *const *const u8,
+  vrf_input_lens: *const size_t,
+  contexts: *const *const u8,
+  context_lens: *const size_t,
+  signatures: *const u8,
+  count: size_t,
+  output_hashes_out: *mut u8,
+  results: *mut u8,
+) -> c_int {
+  if count == 0 {
+    return 0;
+  }
+
+  if public_keys.is_null()
+    || vrf_inputs.is_null()
+    || vrf_input_lens.is_null()
+    || contexts.is_null()
+    || context_lens.is_null()
+    || signatures.is_null()
+    || output_hashes_out.is_null()
+  {
+    return -1;
+  }
+
+  let pks = slice::from_raw_parts(public_keys, count * PUBLIC_LENGTH);
+  let sigs = slice::from_raw_parts(signatures, count * SIGNATURE_LENGTH);
+  let input_ptrs = slice::from_raw_parts(vrf_inputs, count);
+  let input_lens = slice::from_raw_parts(vrf_input_lens, count);
+  let context_ptrs = slice::from_raw_parts(contexts, count);
+  let context_lens = slice::from_raw_parts(context_lens, count);
+
+  let mut items = Vec::with_capacity(count);
+  for i in 0..count {
+    let (Some(vrf_input), Some(context)) = (
+      optional_slice(input_ptrs[i], input_lens[i]),
+      optional_slice(context_ptrs[i], context_lens[i]),
+    ) else {
+      return -1;
+    };
+    let public_key = pks[i * PUBLIC_LENGTH..(i + 1) * PUBLIC_LENGTH]
+      .try_into()
+      .expect("slice has public key length");
+    let signature = sigs[i * SIGNATURE_LENGTH..(i + 1) * SIGNATURE_LENGTH]
+      .try_into()
+      .expect("slice has signature length");
+    items.push((public_key, vrf_input, context, signature));
+  }
+
+  let hashes = bandersnatch_verify_batch_impl(&items);
+
+  let out = slice::from_raw_parts_mut(output_hashes_out, count * OUTPUT_LENGTH);
+  for (chunk, hash) in out.chunks_exact_mut(OUTPUT_LENGTH).zip(&hashes) {
+    chunk.copy_from_slice(&hash.unwrap_or_default());
+  }
+  if !results.is_null() {
+    let results = slice::from_raw_parts_mut(results, count);
+    for (result, hash) in results.iter_mut().zip(&hashes) {
+      *result = hash.is_some() as u8;
+    }
+  }
+
+  if hashes.iter().all(Option::is_some) {
+    0
+  } else {
+    -1
+  }
+}
+
 /// Extracts the VRF output hash Y(s) from a signature according to equation G.2
 ///
 /// Writes the output hash to output_hash_out which must be BANDERSNATCH_OUTPUT_LENGTH bytes
@@ -285,7 +537,7 @@ mod tests {
 
     // Create and verify a non-anonymous VRF signature
     let signature = bandersnatch_sign_impl(
-      secret.clone(),
+      &secret,
       "message".as_bytes(),
       "context".as_bytes(),
     );
@@ -299,4 +551,144 @@ mod tests {
     )
     .unwrap();
   }
+
+  #[test]
+  fn test_secret_handle_matches_raw_secret() {
+    let seed = 42_usize.to_le_bytes();
+    let mut raw_secret = [0u8; SECRET_LENGTH];
+    let mut raw_public = [0u8; PUBLIC_LENGTH];
+    let mut handle_public = [0u8; PUBLIC_LENGTH];
+    let mut raw_sig = [0u8; SIGNATURE_LENGTH];
+    let mut handle_sig = [0u8; SIGNATURE_LENGTH];
+    let mut output_hash = [0u8; OUTPUT_LENGTH];
+    let (input, context) = (b"message", b"context");
+
+    unsafe {
+      let handle = bandersnatch_secret_from_seed(seed.as_ptr(), seed.len());
+      assert!(!handle.is_null());
+
+      assert_eq!(
+        bandersnatch_new_secret(
+          seed.as_ptr(),
+          seed.len(),
+          raw_secret.as_mut_ptr()
+        ),
+        0
+      );
+      assert_eq!(
+        bandersnatch_derive_public(
+          raw_secret.as_ptr(),
+          raw_public.as_mut_ptr()
+        ),
+        0
+      );
+      assert_eq!(
+        bandersnatch_secret_public(handle, handle_public.as_mut_ptr()),
+        0
+      );
+      assert_eq!(raw_public, handle_public);
+
+      // IETF VRF signatures are deterministic
+      assert_eq!(
+        bandersnatch_sign(
+          raw_secret.as_ptr(),
+          input.as_ptr(),
+          input.len(),
+          context.as_ptr(),
+          context.len(),
+          raw_sig.as_mut_ptr(),
+        ),
+        0
+      );
+      assert_eq!(
+        bandersnatch_secret_sign(
+          handle,
+          input.as_ptr(),
+          input.len(),
+          context.as_ptr(),
+          context.len(),
+          handle_sig.as_mut_ptr(),
+        ),
+        0
+      );
+      assert_eq!(raw_sig, handle_sig);
+
+      assert_eq!(
+        bandersnatch_verify(
+          handle_public.as_ptr(),
+          input.as_ptr(),
+          input.len(),
+          context.as_ptr(),
+          context.len(),
+          handle_sig.as_ptr(),
+          output_hash.as_mut_ptr(),
+        ),
+        0
+      );
+
+      bandersnatch_free_secret(handle);
+    }
+  }
+
+  #[test]
+  fn test_verify_batch_reports_failed_entries() {
+    let secrets: Vec<Secret> = (0..4_usize)
+      .map(|i| Secret::from_seed(&i.to_le_bytes()))
+      .collect();
+    let inputs: Vec<Vec<u8>> = (0..4)
+      .map(|i| format!("entropy {i}").into_bytes())
+      .collect();
+    let context = b"jam_entropy";
+
+    let mut public_keys = Vec::new();
+    let mut signatures = Vec::new();
+    for (secret, input) in secrets.iter().zip(&inputs) {
+      secret
+        .public()
+        .serialize_compressed(&mut public_keys)
+        .unwrap();
+      bandersnatch_sign_impl(secret, input, context)
+        .serialize_compressed(&mut signatures)
+        .unwrap();
+    }
+    // Entry 1 is signed over the wrong input
+    let input_ptrs: Vec<*const u8> = [0, 2, 2, 3]
+      .iter()
+      .map(|&i: &usize| inputs[i].as_ptr())
+      .collect();
+    let input_lens: Vec<usize> = inputs.iter().map(|i| i.len()).collect();
+    let context_ptrs = [context.as_ptr(); 4];
+    let context_lens = [context.len(); 4];
+    let mut hashes = [0xffu8; 4 * OUTPUT_LENGTH];
+    let mut results = [0u8; 4];
+
+    unsafe {
+      let status = bandersnatch_verify_batch(
+        public_keys.as_ptr(),
+        input_ptrs.as_ptr(),
+        input_lens.as_ptr(),
+        context_ptrs.as_ptr(),
+        context_lens.as_ptr(),
+        signatures.as_ptr(),
+        4,
+        hashes.as_mut_ptr(),
+        results.as_mut_ptr(),
+      );
+      assert_eq!(status, -1);
+      assert_eq!(results, [1, 0, 1, 1]);
+      assert_eq!(hashes[32..64], [0u8; 32]);
+
+      for i in [0, 2, 3] {
+        let mut expected = [0u8; OUTPUT_LENGTH];
+        assert_eq!(
+          bandersnatch_output_hash(
+            signatures[i * SIGNATURE_LENGTH..].as_ptr(),
+            expected.as_mut_ptr(),
+          ),
+          0
+        );
+        assert_eq!(hashes[i * 32..(i + 1) * 32], expected);
+      }
+    }
+  }
 }
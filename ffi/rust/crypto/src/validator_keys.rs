@@ -0,0 +1,260 @@
+//! JIP-5 deterministic validator key derivation
+//!
+//! Dev and test networks derive every validator key from a single 32-byte
+//! seed. Each key type gets its own secret seed, obtained by hashing the
+//! validator seed with a domain-separation prefix:
+//!
+//! - Ed25519: `blake2b_256("jam_val_key_ed25519" ++ seed)`, used as the RFC
+//!   8032 secret key
+//! - Bandersnatch: `blake2b_256("jam_val_key_bandersnatch" ++ seed)`, passed
+//!   to `Secret::from_seed`
+//!
+//! The public keys are returned in the `ValidatorData` layout of
+//! `src/types.zig`: bandersnatch (32) ++ ed25519 (32) ++ bls (144) ++
+//! metadata (128).
+
+use ark_serialize::CanonicalSerialize;
+use ark_vrf::suites::bandersnatch::Secret;
+use ed25519_consensus::SigningKey;
+use libc::c_int;
+
+const SEED_LENGTH: usize = 32;
+const BANDERSNATCH_PUBLIC_LENGTH: usize = 32;
+const ED25519_PUBLIC_LENGTH: usize = 32;
+const BLS_PUBLIC_LENGTH: usize = 144;
+const METADATA_LENGTH: usize = 128;
+
+/// Length of a serialized `ValidatorData` entry.
+pub const VALIDATOR_DATA_LENGTH: usize = BANDERSNATCH_PUBLIC_LENGTH
+  + ED25519_PUBLIC_LENGTH
+  + BLS_PUBLIC_LENGTH
+  + METADATA_LENGTH;
+
+/// Length of the secret seeds output: ed25519 (32) ++ bandersnatch (32).
+pub const SECRET_SEEDS_LENGTH: usize = 2 * SEED_LENGTH;
+
+const ED25519_DOMAIN: &[u8] = b"jam_val_key_ed25519";
+const BANDERSNATCH_DOMAIN: &[u8] = b"jam_val_key_bandersnatch";
+
+fn derive_seed(domain: &[u8], seed: &[u8; SEED_LENGTH]) -> [u8; SEED_LENGTH] {
+  let mut state = blake2b_simd::Params::new().hash_length(32).to_state();
+  state.update(domain);
+  state.update(seed);
+  state
+    .finalize()
+    .as_bytes()
+    .try_into()
+    .expect("hash length is 32")
+}
+
+/// Per-key-type secret seeds of a validator.
+pub struct SecretSeeds {
+  /// RFC 8032 Ed25519 secret key
+  pub ed25519: [u8; SEED_LENGTH],
+  /// Seed for the Bandersnatch `Secret::from_seed`
+  pub bandersnatch: [u8; SEED_LENGTH],
+}
+
+impl SecretSeeds {
+  /// Derives the per-key-type secret seeds from a validator seed.
+  pub fn derive(seed: &[u8; SEED_LENGTH]) -> Self {
+    Self {
+      ed25519: derive_seed(ED25519_DOMAIN, seed),
+      bandersnatch: derive_seed(BANDERSNATCH_DOMAIN, seed),
+    }
+  }
+
+  /// Public keys in the `ValidatorData` layout.
+  ///
+  /// JIP-5 does not define a BLS derivation and this crate has no BLS
+  /// backend, so the BLS key is left zeroed, as is the metadata, which
+  /// carries the validator's network endpoint and is up to the caller.
+  pub fn validator_data(&self) -> [u8; VALIDATOR_DATA_LENGTH] {
+    let mut out = [0u8; VALIDATOR_DATA_LENGTH];
+
+    Secret::from_seed(&self.bandersnatch)
+      .public()
+      .serialize_compressed(&mut out[..BANDERSNATCH_PUBLIC_LENGTH])
+      .expect("public key fits its slot");
+
+    let ed25519 = SigningKey::from(self.ed25519).verification_key();
+    out[BANDERSNATCH_PUBLIC_LENGTH
+      ..BANDERSNATCH_PUBLIC_LENGTH + ED25519_PUBLIC_LENGTH]
+      .copy_from_slice(ed25519.as_bytes());
+
+    out
+  }
+}
+
+/// JIP-5 trivial seed of dev validator `index`: `index` as 32-bit little
+/// endian, repeated eight times.
+pub fn trivial_seed(index: u32) -> [u8; SEED_LENGTH] {
+  let mut seed = [0u8; SEED_LENGTH];
+  for chunk in seed.chunks_exact_mut(4) {
+    chunk.copy_from_slice(&index.to_le_bytes());
+  }
+  seed
+}
+
+/// Derives a validator's key bundle from a 32-byte seed.
+///
+/// # Arguments
+/// * `seed` - 32-byte validator seed
+/// * `validator_data_out` - Buffer receiving the 336-byte `ValidatorData`
+///   entry (bandersnatch ++ ed25519 ++ bls ++ metadata; bls and metadata
+///   zeroed)
+/// * `secret_seeds_out` - Optional buffer receiving the 64-byte secret seeds
+///   (ed25519 ++ bandersnatch); may be null
+///
+/// The Ed25519 secret seed is accepted by `ed25519_sign`, the Bandersnatch one
+/// by `bandersnatch_secret_from_seed` and `bandersnatch_new_secret`.
+///
+/// # Returns
+/// * `0` - Keys written
+/// * `-1` - Inputs are malformed
+///
+/// # Safety
+/// Caller must ensure all pointers are valid and point to appropriately sized buffers.
+#[no_mangle]
+pub unsafe extern "C" fn validator_keys_from_seed(
+  seed: *const u8,
+  validator_data_out: *mut u8,
+  secret_seeds_out: *mut u8,
+) -> c_int {
+  if seed.is_null() || validator_data_out.is_null() {
+    return -1;
+  }
+
+  let seed: [u8; SEED_LENGTH] =
+    match std::slice::from_raw_parts(seed, SEED_LENGTH).try_into() {
+      Ok(bytes) => bytes,
+      Err(_) => return -1,
+    };
+
+  let secrets = SecretSeeds::derive(&seed);
+  let data = secrets.validator_data();
+  std::ptr::copy_nonoverlapping(
+    data.as_ptr(),
+    validator_data_out,
+    VALIDATOR_DATA_LENGTH,
+  );
+
+  if !secret_seeds_out.is_null() {
+    std::ptr::copy_nonoverlapping(
+      secrets.ed25519.as_ptr(),
+      secret_seeds_out,
+      SEED_LENGTH,
+    );
+    std::ptr::copy_nonoverlapping(
+      secrets.bandersnatch.as_ptr(),
+      secret_seeds_out.add(SEED_LENGTH),
+      SEED_LENGTH,
+    );
+  }
+
+  0
+}
+
+/// Writes the JIP-5 trivial seed of dev validator `index` to `seed_out`.
+///
+/// # Returns
+/// * `0` - Seed written
+/// * `-1` - `seed_out` is null
+///
+/// # Safety
+/// `seed_out` must point to 32 writable bytes.
+#[no_mangle]
+pub unsafe extern "C" fn validator_trivial_seed(
+  index: u32,
+  seed_out: *mut u8,
+) -> c_int {
+  if seed_out.is_null() {
+    return -1;
+  }
+
+  std::ptr::copy_nonoverlapping(
+    trivial_seed(index).as_ptr(),
+    seed_out,
+    SEED_LENGTH,
+  );
+
+  0
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  struct DevKey {
+    index: u32,
+    ed25519_secret: &'static str,
+    ed25519_public: &'static str,
+    bandersnatch_secret: &'static str,
+    bandersnatch_public: &'static str,
+  }
+
+  /// Published JIP-5 dev keys (Alice, Bob).
+  const DEV_KEYS: [DevKey; 2] = [
+    DevKey {
+      index: 0,
+      ed25519_secret:
+        "996542becdf1e78278dc795679c825faca2e9ed2bf101bf3c4a236d3ed79cf59",
+      ed25519_public:
+        "4418fb8c85bb3985394a8c2756d3643457ce614546202a2f50b093d762499ace",
+      bandersnatch_secret:
+        "007596986419e027e65499cc87027a236bf4a78b5e8bd7f675759d73e7a9c799",
+      bandersnatch_public:
+        "ff71c6c03ff88adb5ed52c9681de1629a54e702fc14729f6b50d2f0a76f185b3",
+    },
+    DevKey {
+      index: 1,
+      ed25519_secret:
+        "b81e308145d97464d2bc92d35d227a9e62241a16451af6da5053e309be4f91d7",
+      ed25519_public:
+        "ad93247bd01307550ec7acd757ce6fb805fcf73db364063265b30a949e90d933",
+      bandersnatch_secret:
+        "12ca375c9242101c99ad5fafe8997411f112ae10e0e5b7c4589e107c433700ac",
+      bandersnatch_public:
+        "dee6d555b82024f1ccf8a1e37e60fa60fd40b1958c4bb3006af78647950e1b91",
+    },
+  ];
+
+  #[test]
+  fn test_dev_key_fixtures() {
+    for key in &DEV_KEYS {
+      let mut seed = [0u8; SEED_LENGTH];
+      let mut data = [0u8; VALIDATOR_DATA_LENGTH];
+      let mut secrets = [0u8; SECRET_SEEDS_LENGTH];
+
+      unsafe {
+        assert_eq!(validator_trivial_seed(key.index, seed.as_mut_ptr()), 0);
+        assert_eq!(
+          validator_keys_from_seed(
+            seed.as_ptr(),
+            data.as_mut_ptr(),
+            secrets.as_mut_ptr()
+          ),
+          0
+        );
+      }
+
+      assert_eq!(hex::encode(&secrets[..32]), key.ed25519_secret);
+      assert_eq!(hex::encode(&secrets[32..]), key.bandersnatch_secret);
+      assert_eq!(hex::encode(&data[..32]), key.bandersnatch_public);
+      assert_eq!(hex::encode(&data[32..64]), key.ed25519_public);
+      assert!(
+        data[64..].iter().all(|&b| b == 0),
+        "bls and metadata zeroed"
+      );
+    }
+  }
+
+  #[test]
+  fn test_trivial_seed() {
+    assert_eq!(trivial_seed(0), [0u8; 32]);
+    assert_eq!(
+      hex::encode(trivial_seed(1)),
+      "0100000001000000010000000100000001000000010000000100000001000000"
+    );
+  }
+}
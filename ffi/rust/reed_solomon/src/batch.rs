@@ -0,0 +1,152 @@
+//! Batch erasure encoding of independent blobs
+//!
+//! A block can carry one report per core, and each work-package bundle and
+//! segment batch is encoded independently. With the `parallel` feature the
+//! blobs are spread over a rayon thread pool; without it they are encoded
+//! one after another.
+
+use crate::{
+  codec::{self, Error},
+  config::ErasureConfig,
+};
+
+/// Number of CPUs available to the process, at least 1.
+#[cfg(feature = "parallel")]
+fn available_cpus() -> usize {
+  std::thread::available_parallelism().map_or(1, std::num::NonZeroUsize::get)
+}
+
+/// Returns a thread pool with `max_threads` workers, built on first use and
+/// kept for later calls with the same cap.
+///
+/// `max_threads` is clamped to the available CPUs, so at most one pool per
+/// CPU count is ever kept, whatever callers pass.
+#[cfg(feature = "parallel")]
+fn capped_pool(
+  max_threads: usize,
+) -> Result<std::sync::Arc<rayon::ThreadPool>, Error> {
+  use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex, OnceLock},
+  };
+
+  static POOLS: OnceLock<Mutex<HashMap<usize, Arc<rayon::ThreadPool>>>> =
+    OnceLock::new();
+
+  let max_threads = max_threads.min(available_cpus());
+  let mut pools = POOLS
+    .get_or_init(Default::default)
+    .lock()
+    .unwrap_or_else(|e| e.into_inner());
+  if let Some(pool) = pools.get(&max_threads) {
+    return Ok(pool.clone());
+  }
+
+  let pool = rayon::ThreadPoolBuilder::new()
+    .num_threads(max_threads)
+    .build()
+    .map_err(|e| Error::ThreadPool(e.to_string()))?;
+  let pool = Arc::new(pool);
+  pools.insert(max_threads, pool.clone());
+  Ok(pool)
+}
+
+/// Erasure-codes every blob in `blobs`, returning the chunks of each blob in
+/// input order.
+///
+/// `max_threads` caps the number of worker threads and is itself capped at
+/// the available CPUs; `0` runs on rayon's global pool, which has one thread
+/// per available CPU. The first error
+/// encountered is returned.
+#[cfg(feature = "parallel")]
+pub fn encode_batch(
+  config: &ErasureConfig,
+  blobs: &[&[u8]],
+  max_threads: usize,
+) -> Result<Vec<Vec<Vec<u8>>>, Error> {
+  use rayon::prelude::*;
+
+  let encode = || {
+    blobs
+      .par_iter()
+      .map(|blob| codec::encode(config, blob))
+      .collect()
+  };
+
+  if max_threads == 0 {
+    encode()
+  } else {
+    capped_pool(max_threads)?.install(encode)
+  }
+}
+
+/// Erasure-codes every blob in `blobs`, returning the chunks of each blob in
+/// input order.
+///
+/// Built without the `parallel` feature, so `max_threads` is ignored.
+#[cfg(not(feature = "parallel"))]
+pub fn encode_batch(
+  config: &ErasureConfig,
+  blobs: &[&[u8]],
+  _max_threads: usize,
+) -> Result<Vec<Vec<Vec<u8>>>, Error> {
+  blobs
+    .iter()
+    .map(|blob| codec::encode(config, blob))
+    .collect()
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_encode_batch_matches_serial_encoding() {
+    let config = ErasureConfig::tiny();
+    let blobs: Vec<Vec<u8>> = (1..=8)
+      .map(|n| (0..n * 100).map(|i| (i * n) as u8).collect())
+      .collect();
+    let refs: Vec<&[u8]> = blobs.iter().map(|b| &b[..]).collect();
+
+    for max_threads in [0, 1, 3] {
+      let encoded = encode_batch(&config, &refs, max_threads).unwrap();
+      assert_eq!(encoded.len(), blobs.len());
+
+      for (blob, chunks) in blobs.iter().zip(&encoded) {
+        assert_eq!(chunks, &codec::encode(&config, blob).unwrap());
+      }
+    }
+  }
+
+  #[cfg(feature = "parallel")]
+  #[test]
+  fn test_capped_pool_is_reused() {
+    let cpus = available_cpus();
+    let pool = capped_pool(cpus).unwrap();
+    assert_eq!(pool.current_num_threads(), cpus);
+    assert!(std::sync::Arc::ptr_eq(&pool, &capped_pool(cpus).unwrap()));
+  }
+
+  #[cfg(feature = "parallel")]
+  #[test]
+  fn test_capped_pool_is_clamped_to_cpus() {
+    let cpus = available_cpus();
+    let pool = capped_pool(cpus + 1).unwrap();
+    assert_eq!(pool.current_num_threads(), cpus);
+    assert!(std::sync::Arc::ptr_eq(
+      &pool,
+      &capped_pool(usize::MAX).unwrap()
+    ));
+  }
+
+  #[test]
+  fn test_encode_batch_reports_errors() {
+    let config = ErasureConfig::tiny();
+    let blobs: [&[u8]; 2] = [b"fine", b""];
+
+    assert!(matches!(
+      encode_batch(&config, &blobs, 2),
+      Err(Error::EmptyData)
+    ));
+  }
+}
@@ -0,0 +1,250 @@
+//! JAM erasure coding on top of parity's `erasure-coding` crate
+//!
+//! Data blobs are zero-padded to a multiple of the erasure-coded piece size
+//! W_E and coded into one chunk per validator, as described in appendix H of
+//! the Gray Paper.
+
+use erasure_coding::ChunkIndex;
+use thiserror::Error;
+
+use crate::config::ErasureConfig;
+
+#[derive(Error, Debug)]
+pub enum Error {
+  #[error("Data must not be empty")]
+  EmptyData,
+  #[error("Segments must be exactly 4104 bytes")]
+  InvalidSegmentSize,
+  #[error("Segment index {0} is out of range")]
+  SegmentIndexOutOfRange(usize),
+  #[error("Encoder produced an unexpected chunk layout")]
+  UnexpectedChunkLayout,
+  #[error("Not enough chunks: got {got}, need {needed}")]
+  NotEnoughChunks { got: usize, needed: usize },
+  #[error("Chunk index {0} is out of range")]
+  ChunkIndexOutOfRange(u16),
+  #[error("Chunk index {0} was supplied more than once")]
+  DuplicateChunkIndex(u16),
+  #[error("Chunk {index} has length {len}, expected {expected}")]
+  InconsistentChunkLength {
+    index: u16,
+    len: usize,
+    expected: usize,
+  },
+  #[error("Erasure coding failed: {0}")]
+  ErasureCoding(String),
+  #[error("Failed to build thread pool: {0}")]
+  ThreadPool(String),
+}
+
+/// Erasure-codes `data` into one equally sized chunk per validator.
+///
+/// The data is zero-padded to a multiple of W_E before coding, so the first
+/// chunks carry the original data and the remainder carry the redundancy.
+pub fn encode(
+  config: &ErasureConfig,
+  data: &[u8],
+) -> Result<Vec<Vec<u8>>, Error> {
+  if data.is_empty() {
+    return Err(Error::EmptyData);
+  }
+
+  let n_chunks = config.chunk_count();
+  let expected_chunk_len = config.chunk_len(data.len());
+
+  let mut padded = data.to_vec();
+  padded.resize(config.padded_len(data.len()), 0);
+
+  let chunks = erasure_coding::construct_chunks(n_chunks, &padded)
+    .map_err(|e| Error::ErasureCoding(e.to_string()))?;
+
+  if chunks.len() != n_chunks as usize
+    || chunks.iter().any(|c| c.len() != expected_chunk_len)
+  {
+    return Err(Error::UnexpectedChunkLayout);
+  }
+
+  Ok(chunks)
+}
+
+/// Reconstructs the original `data_len` bytes from a subset of chunks.
+///
+/// `chunks` yields `(index, chunk)` pairs. At least the configured recovery
+/// threshold of distinct chunks is required; all of them must have the
+/// length produced by `encode` for `data_len` bytes.
+pub fn reconstruct<'a>(
+  config: &ErasureConfig,
+  chunks: impl IntoIterator<Item = (u16, &'a [u8])>,
+  data_len: usize,
+) -> Result<Vec<u8>, Error> {
+  if data_len == 0 {
+    return Err(Error::EmptyData);
+  }
+
+  let n_chunks = config.chunk_count();
+  let needed = config.recovery_threshold();
+  let expected_chunk_len = config.chunk_len(data_len);
+
+  let mut seen = vec![false; n_chunks as usize];
+  let mut supplied = Vec::with_capacity(needed);
+  for (index, chunk) in chunks {
+    if index >= n_chunks {
+      return Err(Error::ChunkIndexOutOfRange(index));
+    }
+    if seen[index as usize] {
+      return Err(Error::DuplicateChunkIndex(index));
+    }
+    if chunk.len() != expected_chunk_len {
+      return Err(Error::InconsistentChunkLength {
+        index,
+        len: chunk.len(),
+        expected: expected_chunk_len,
+      });
+    }
+    seen[index as usize] = true;
+    supplied.push((ChunkIndex(index), chunk.to_vec()));
+  }
+
+  if supplied.len() < needed {
+    return Err(Error::NotEnoughChunks {
+      got: supplied.len(),
+      needed,
+    });
+  }
+
+  if config
+    .systematic_indices()
+    .all(|index| seen[index as usize])
+  {
+    return Ok(from_systematic(config, &supplied, data_len));
+  }
+
+  let padded_len = config.padded_len(data_len);
+  let mut data = erasure_coding::reconstruct(n_chunks, supplied, padded_len)
+    .map_err(|e| Error::ErasureCoding(e.to_string()))?;
+  data.truncate(data_len);
+
+  Ok(data)
+}
+
+/// Rebuilds the data from the systematic chunks alone.
+///
+/// Systematic chunk `i` holds word `i` of every piece, so each piece is the
+/// concatenation of the matching word of chunks `0..recovery_threshold`.
+fn from_systematic(
+  config: &ErasureConfig,
+  chunks: &[(ChunkIndex, Vec<u8>)],
+  data_len: usize,
+) -> Vec<u8> {
+  let mut ordered: Vec<&[u8]> = vec![&[]; config.recovery_threshold()];
+  for (ChunkIndex(index), chunk) in chunks {
+    if config.is_systematic(*index) {
+      ordered[*index as usize] = chunk;
+    }
+  }
+
+  let pieces = config.chunk_len(data_len) / 2;
+  let mut data = Vec::with_capacity(pieces * config.piece_size());
+  for piece in 0..pieces {
+    for chunk in &ordered {
+      data.extend_from_slice(&chunk[2 * piece..2 * piece + 2]);
+    }
+  }
+  data.truncate(data_len);
+
+  data
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_encode_produces_one_chunk_per_validator() {
+    let data: Vec<u8> = (0..1000u32).map(|i| i as u8).collect();
+
+    for config in [ErasureConfig::tiny(), ErasureConfig::full()] {
+      let chunks = encode(&config, &data).unwrap();
+      let expected_len = config.chunk_len(data.len());
+
+      assert_eq!(chunks.len(), config.chunk_count() as usize);
+      assert!(chunks.iter().all(|c| c.len() == expected_len));
+    }
+  }
+
+  #[test]
+  fn test_reconstruct_from_parity_chunks() {
+    let data: Vec<u8> = (0..1000u32).map(|i| (i * 7) as u8).collect();
+
+    for config in [ErasureConfig::tiny(), ErasureConfig::full()] {
+      let chunks = encode(&config, &data).unwrap();
+
+      // Take the last chunks so no original data chunk is used
+      let subset = chunks
+        .iter()
+        .enumerate()
+        .rev()
+        .take(config.recovery_threshold())
+        .map(|(i, c)| (i as u16, c.as_slice()));
+
+      assert_eq!(reconstruct(&config, subset, data.len()).unwrap(), data);
+    }
+  }
+
+  #[test]
+  fn test_reconstruct_from_systematic_chunks() {
+    let data: Vec<u8> = (0..1000u32).map(|i| (i * 13) as u8).collect();
+
+    for config in [ErasureConfig::tiny(), ErasureConfig::full()] {
+      let chunks = encode(&config, &data).unwrap();
+
+      // Systematic chunks in reverse order plus one parity chunk
+      let subset = config
+        .systematic_indices()
+        .rev()
+        .chain([config.chunk_count() - 1])
+        .map(|i| (i, chunks[i as usize].as_slice()));
+
+      assert_eq!(reconstruct(&config, subset, data.len()).unwrap(), data);
+    }
+  }
+
+  #[test]
+  fn test_reconstruct_reports_bad_input() {
+    let config = ErasureConfig::tiny();
+    let data = [42u8; 100];
+    let chunks = encode(&config, &data).unwrap();
+
+    let too_few = [(3, chunks[3].as_slice())];
+    assert!(matches!(
+      reconstruct(&config, too_few, data.len()),
+      Err(Error::NotEnoughChunks { got: 1, needed: 2 })
+    ));
+
+    let duplicate = [(3, chunks[3].as_slice()), (3, chunks[3].as_slice())];
+    assert!(matches!(
+      reconstruct(&config, duplicate, data.len()),
+      Err(Error::DuplicateChunkIndex(3))
+    ));
+
+    let out_of_range = [(6, chunks[0].as_slice())];
+    assert!(matches!(
+      reconstruct(&config, out_of_range, data.len()),
+      Err(Error::ChunkIndexOutOfRange(6))
+    ));
+
+    let truncated = [(0, &chunks[0][1..]), (1, chunks[1].as_slice())];
+    assert!(matches!(
+      reconstruct(&config, truncated, data.len()),
+      Err(Error::InconsistentChunkLength { index: 0, .. })
+    ));
+  }
+
+  #[test]
+  fn test_encode_empty_data_fails() {
+    assert!(matches!(
+      encode(&ErasureConfig::tiny(), &[]),
+      Err(Error::EmptyData)
+    ));
+  }
+}
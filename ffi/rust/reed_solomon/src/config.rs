@@ -0,0 +1,190 @@
+//! Chain-spec driven erasure coding parameters
+//!
+//! The number of chunks, the recovery threshold and the erasure-coded piece
+//! size W_E all follow from the validator count, so the same binary can run
+//! the tiny testnet and full-size conformance. The core count does not affect
+//! the coding and is not part of the configuration.
+
+use std::ops::Range;
+
+use thiserror::Error;
+
+/// Size of an exported segment W_G in octets.
+pub const SEGMENT_SIZE: usize = 4104;
+
+/// Validator count of the full chain spec.
+pub const FULL_VALIDATORS_COUNT: u16 = 1023;
+/// Validator count of the tiny chain spec.
+pub const TINY_VALIDATORS_COUNT: u16 = 6;
+
+#[derive(Error, Debug)]
+pub enum ConfigError {
+  #[error("At least two validators are required")]
+  NotEnoughValidators,
+  #[error("No recovery threshold fits the segment size")]
+  NoValidThreshold,
+  #[error("Encoder rejected the validator count: {0}")]
+  Encoder(String),
+  #[error("Recovery threshold {threshold} differs from encoder's {encoder}")]
+  ThresholdMismatch { threshold: usize, encoder: usize },
+}
+
+/// Erasure coding parameters for one chain spec.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ErasureConfig {
+  /// Number of validators, which is also the number of chunks
+  validators_count: u16,
+  /// Number of distinct chunks needed to reconstruct the data
+  recovery_threshold: usize,
+  /// Erasure-coded piece size W_E in octets
+  piece_size: usize,
+}
+
+impl ErasureConfig {
+  /// Builds the configuration for `validators_count` validators.
+  ///
+  /// The recovery threshold is the smallest word count of at least a third
+  /// of the validators whose piece size W_E = 2 * threshold evenly divides a
+  /// segment. This gives 342 of 1023 (W_E = 684) for full and 2 of 6
+  /// (W_E = 4) for tiny. Validator counts for which the encoder works with a
+  /// different threshold are rejected, since chunks would then not line up
+  /// with W_E.
+  pub fn new(validators_count: u16) -> Result<Self, ConfigError> {
+    if validators_count < 2 {
+      return Err(ConfigError::NotEnoughValidators);
+    }
+
+    let min_threshold = (validators_count as usize).div_ceil(3);
+    let recovery_threshold = (min_threshold..validators_count as usize)
+      .find(|k| SEGMENT_SIZE.is_multiple_of(2 * k))
+      .ok_or(ConfigError::NoValidThreshold)?;
+
+    let encoder = erasure_coding::recovery_threshold(validators_count)
+      .map_err(|e| ConfigError::Encoder(e.to_string()))?
+      as usize;
+    if encoder != recovery_threshold {
+      return Err(ConfigError::ThresholdMismatch {
+        threshold: recovery_threshold,
+        encoder,
+      });
+    }
+
+    Ok(Self {
+      validators_count,
+      recovery_threshold,
+      piece_size: 2 * recovery_threshold,
+    })
+  }
+
+  /// Configuration of the full chain spec.
+  pub fn full() -> Self {
+    Self::new(FULL_VALIDATORS_COUNT)
+      .expect("full chain spec parameters are valid")
+  }
+
+  /// Configuration of the tiny chain spec.
+  pub fn tiny() -> Self {
+    Self::new(TINY_VALIDATORS_COUNT)
+      .expect("tiny chain spec parameters are valid")
+  }
+
+  /// Returns the number of chunks produced, one per validator.
+  pub fn chunk_count(&self) -> u16 {
+    self.validators_count
+  }
+
+  /// Returns the number of distinct chunks needed to reconstruct the data.
+  pub fn recovery_threshold(&self) -> usize {
+    self.recovery_threshold
+  }
+
+  /// Returns the indices of the systematic chunks.
+  ///
+  /// The first `recovery_threshold` chunks carry the original data words
+  /// unchanged, so holding all of them avoids a full decode.
+  pub fn systematic_indices(&self) -> Range<u16> {
+    0..self.recovery_threshold as u16
+  }
+
+  /// Returns whether chunk `index` is a systematic data chunk.
+  pub fn is_systematic(&self, index: u16) -> bool {
+    self.systematic_indices().contains(&index)
+  }
+
+  /// Returns the erasure-coded piece size W_E in octets.
+  pub fn piece_size(&self) -> usize {
+    self.piece_size
+  }
+
+  /// Returns the size in bytes of every chunk produced for `data_len` bytes.
+  ///
+  /// Each W_E-sized piece of the padded data contributes one 2-byte word to
+  /// every chunk.
+  pub fn chunk_len(&self, data_len: usize) -> usize {
+    data_len.div_ceil(self.piece_size) * 2
+  }
+
+  /// Returns `data_len` rounded up to a whole number of pieces.
+  pub fn padded_len(&self, data_len: usize) -> usize {
+    data_len.div_ceil(self.piece_size) * self.piece_size
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_chain_spec_parameters() {
+    let full = ErasureConfig::full();
+    assert_eq!(full.chunk_count(), 1023);
+    assert_eq!(full.recovery_threshold(), 342);
+    assert_eq!(full.piece_size(), 684);
+
+    let tiny = ErasureConfig::tiny();
+    assert_eq!(tiny.chunk_count(), 6);
+    assert_eq!(tiny.recovery_threshold(), 2);
+    assert_eq!(tiny.piece_size(), 4);
+    assert_eq!(tiny.systematic_indices(), 0..2);
+    assert!(tiny.is_systematic(1));
+    assert!(!tiny.is_systematic(2));
+  }
+
+  #[test]
+  fn test_chunk_len() {
+    let tiny = ErasureConfig::tiny();
+    assert_eq!(tiny.chunk_len(1), 2);
+    assert_eq!(tiny.chunk_len(4), 2);
+    assert_eq!(tiny.chunk_len(5), 4);
+
+    let full = ErasureConfig::full();
+    assert_eq!(full.chunk_len(684), 2);
+    assert_eq!(full.chunk_len(SEGMENT_SIZE), 12);
+  }
+
+  #[test]
+  fn test_invalid_parameters() {
+    assert!(matches!(
+      ErasureConfig::new(1),
+      Err(ConfigError::NotEnoughValidators)
+    ));
+  }
+
+  #[test]
+  fn test_threshold_matches_encoder() {
+    for config in [ErasureConfig::tiny(), ErasureConfig::full()] {
+      let encoder =
+        erasure_coding::recovery_threshold(config.chunk_count()).unwrap();
+      assert_eq!(config.recovery_threshold(), encoder as usize);
+    }
+
+    // A third of 30 is 10, but W_E = 20 does not divide a segment
+    assert!(matches!(
+      ErasureConfig::new(30),
+      Err(ConfigError::ThresholdMismatch {
+        threshold: 12,
+        encoder: 10
+      })
+    ));
+  }
+}
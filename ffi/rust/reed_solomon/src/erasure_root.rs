@@ -0,0 +1,178 @@
+//! Erasure root of a work-package bundle and its exported segments
+//!
+//! Every validator receives one bundle chunk and one chunk of each exported
+//! segment. The leaf committed for validator `i` is the hash of its bundle
+//! chunk followed by its segment chunks in segment order, and the erasure
+//! root is the well-balanced Merkle root over all leaves.
+//!
+//! The segments committed to are the exported segments s followed by their
+//! paged proofs P(s), one proof segment per page of 64 exported segments
+//! (Gray Paper section 14.4). Paged proofs are built from the constant-depth
+//! segment tree, which lives on the Zig side, so this module takes s ⌢ P(s)
+//! as given. A segment list without the paged proofs yields a root that does
+//! not match the one in the work report.
+
+use crate::{
+  codec::{self, Error},
+  config::ErasureConfig,
+  merkle::{self, Hash, HASH_LENGTH},
+  segment,
+};
+
+/// Chunks of an encoded bundle together with their commitment.
+pub struct EncodedBundle {
+  /// Bundle chunk of every validator
+  pub bundle_chunks: Vec<Vec<u8>>,
+  /// Segment chunks of every validator, concatenated in segment order
+  pub segment_chunks: Vec<Vec<u8>>,
+  /// Well-balanced Merkle root over all validator leaves
+  pub erasure_root: Hash,
+  /// Justification of every validator leaf against `erasure_root`
+  pub justifications: Vec<Vec<u8>>,
+}
+
+/// Returns the length of a validator leaf for `segments_count` segments.
+pub fn leaf_len(config: &ErasureConfig, segments_count: usize) -> usize {
+  HASH_LENGTH + segments_count * segment::segment_chunk_len(config)
+}
+
+/// Builds the leaf committed for one validator.
+pub fn leaf(bundle_chunk: &[u8], segment_chunks: &[u8]) -> Vec<u8> {
+  let mut leaf = merkle::hash(bundle_chunk).to_vec();
+  leaf.extend_from_slice(segment_chunks);
+  leaf
+}
+
+/// Erasure-codes a bundle and its exported segments and commits to the
+/// resulting chunks.
+///
+/// `segments` must be the exported segments followed by their paged proofs,
+/// see the module documentation. Every segment must be exactly
+/// `SEGMENT_SIZE` bytes long.
+pub fn encode_with_root(
+  config: &ErasureConfig,
+  bundle: &[u8],
+  segments: &[&[u8]],
+) -> Result<EncodedBundle, Error> {
+  let bundle_chunks = codec::encode(config, bundle)?;
+  let segment_chunks = segment::encode_segments(config, segments)?;
+
+  let leaves: Vec<Vec<u8>> = bundle_chunks
+    .iter()
+    .zip(&segment_chunks)
+    .map(|(bundle_chunk, segment_chunks)| leaf(bundle_chunk, segment_chunks))
+    .collect();
+  let (erasure_root, justifications) =
+    merkle::root_with_justifications(&leaves);
+
+  Ok(EncodedBundle {
+    bundle_chunks,
+    segment_chunks,
+    erasure_root,
+    justifications,
+  })
+}
+
+/// Checks a validator's chunks against an erasure root.
+///
+/// `segment_chunks` holds the validator's chunk of every exported segment and
+/// paged proof in segment order and is empty for bundles without segments.
+pub fn verify_chunk(
+  config: &ErasureConfig,
+  index: u16,
+  bundle_chunk: &[u8],
+  segment_chunks: &[u8],
+  justification: &[u8],
+  erasure_root: &Hash,
+) -> bool {
+  let segment_chunk_len = segment::segment_chunk_len(config);
+  if !segment_chunks.len().is_multiple_of(segment_chunk_len) {
+    return false;
+  }
+
+  merkle::verify(
+    config.chunk_count() as usize,
+    index as usize,
+    &leaf(bundle_chunk, segment_chunks),
+    justification,
+    erasure_root,
+  )
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::config::SEGMENT_SIZE;
+
+  #[test]
+  fn test_encode_with_root_commits_to_all_chunks() {
+    let config = ErasureConfig::tiny();
+    let bundle = vec![3u8; 500];
+    let segments = [vec![1u8; SEGMENT_SIZE], vec![2u8; SEGMENT_SIZE]];
+    let segment_refs: Vec<&[u8]> = segments.iter().map(|s| &s[..]).collect();
+
+    let encoded = encode_with_root(&config, &bundle, &segment_refs).unwrap();
+
+    assert_eq!(
+      encoded.bundle_chunks,
+      codec::encode(&config, &bundle).unwrap()
+    );
+    assert!(encoded
+      .segment_chunks
+      .iter()
+      .all(|c| c.len() == 2 * config.chunk_len(SEGMENT_SIZE)));
+    assert_eq!(encoded.justifications.len(), config.chunk_count() as usize);
+
+    let leaves: Vec<Vec<u8>> = encoded
+      .bundle_chunks
+      .iter()
+      .zip(&encoded.segment_chunks)
+      .map(|(b, s)| leaf(b, s))
+      .collect();
+    assert!(leaves.iter().all(|l| l.len() == leaf_len(&config, 2)));
+
+    let (root, _) = merkle::root_with_justifications(&leaves);
+    assert_eq!(encoded.erasure_root, root);
+  }
+
+  #[test]
+  fn test_verify_chunk() {
+    let config = ErasureConfig::tiny();
+    let bundle: Vec<u8> = (0..100u8).collect();
+    let segment = vec![7u8; SEGMENT_SIZE];
+    let encoded = encode_with_root(&config, &bundle, &[&segment[..]]).unwrap();
+
+    for i in 0..config.chunk_count() {
+      let i_ = i as usize;
+      assert!(verify_chunk(
+        &config,
+        i,
+        &encoded.bundle_chunks[i_],
+        &encoded.segment_chunks[i_],
+        &encoded.justifications[i_],
+        &encoded.erasure_root,
+      ));
+    }
+
+    // A chunk presented under another validator's index must be rejected
+    assert!(!verify_chunk(
+      &config,
+      1,
+      &encoded.bundle_chunks[0],
+      &encoded.segment_chunks[0],
+      &encoded.justifications[0],
+      &encoded.erasure_root,
+    ));
+  }
+
+  #[test]
+  fn test_encode_with_root_rejects_partial_segment() {
+    let config = ErasureConfig::tiny();
+    let segment = [0u8; 100];
+
+    assert!(matches!(
+      encode_with_root(&config, &[1u8; 10], &[&segment]),
+      Err(Error::InvalidSegmentSize)
+    ));
+  }
+}
@@ -0,0 +1,1256 @@
+//! C ABI for JAM erasure coding
+//!
+//! All coding calls go through an `ErasureConfig` handle created with
+//! `new_erasure_config` for the chain spec in use. They return `ERASURE_OK`
+//! on success and a negative status code on error. Chunks are passed
+//! contiguously in caller-owned buffers, chunk `i` starting at offset
+//! `i * chunk_len`.
+
+use std::{ffi::CStr, ptr, slice};
+
+use libc::{c_char, c_int, size_t};
+
+use crate::{
+  batch,
+  codec::{self, Error},
+  config::{ErasureConfig, SEGMENT_SIZE},
+  erasure_root,
+  merkle::{self, HASH_LENGTH},
+  segment,
+  store::{ChunkStore, StoreError},
+};
+
+/// The call succeeded.
+pub const ERASURE_OK: c_int = 0;
+/// Invalid arguments or an internal erasure coding failure.
+pub const ERASURE_ERROR: c_int = -1;
+/// Fewer distinct chunks than the recovery threshold were supplied.
+pub const ERASURE_NOT_ENOUGH_CHUNKS: c_int = -2;
+/// Chunks were duplicated, out of range or of inconsistent length.
+pub const ERASURE_INCONSISTENT_CHUNKS: c_int = -3;
+/// A chunk does not match the erasure root under its justification.
+pub const ERASURE_INVALID_JUSTIFICATION: c_int = -4;
+/// The requested chunk is not in the availability store.
+pub const ERASURE_NOT_FOUND: c_int = -5;
+/// The availability store could not read or write its files.
+pub const ERASURE_IO_ERROR: c_int = -6;
+
+fn status_code(error: &Error) -> c_int {
+  match error {
+    Error::NotEnoughChunks { .. } => ERASURE_NOT_ENOUGH_CHUNKS,
+    Error::ChunkIndexOutOfRange(_)
+    | Error::DuplicateChunkIndex(_)
+    | Error::InconsistentChunkLength { .. } => ERASURE_INCONSISTENT_CHUNKS,
+    _ => ERASURE_ERROR,
+  }
+}
+
+/// Create an erasure coding configuration for a chain spec.
+///
+/// Returns null if the validator count is not supported.
+#[no_mangle]
+pub extern "C" fn new_erasure_config(
+  validators_count: u16,
+) -> *mut ErasureConfig {
+  match ErasureConfig::new(validators_count) {
+    Ok(config) => Box::into_raw(Box::new(config)),
+    Err(_) => ptr::null_mut(),
+  }
+}
+
+/// Free an erasure coding configuration.
+///
+/// # Safety
+/// - `config` must be a valid pointer returned by new_erasure_config
+#[no_mangle]
+pub unsafe extern "C" fn free_erasure_config(config: *mut ErasureConfig) {
+  debug_assert!(!config.is_null(), "config pointer must not be null");
+  drop(Box::from_raw(config));
+}
+
+/// Returns the number of chunks produced per blob, one per validator.
+///
+/// # Safety
+/// - `config` must be a valid pointer returned by new_erasure_config
+#[no_mangle]
+pub unsafe extern "C" fn erasure_chunk_count(
+  config: *const ErasureConfig,
+) -> u16 {
+  debug_assert!(!config.is_null(), "config pointer must not be null");
+  (*config).chunk_count()
+}
+
+/// Returns the number of distinct chunks needed for reconstruction.
+///
+/// # Safety
+/// - `config` must be a valid pointer returned by new_erasure_config
+#[no_mangle]
+pub unsafe extern "C" fn erasure_recovery_threshold(
+  config: *const ErasureConfig,
+) -> size_t {
+  debug_assert!(!config.is_null(), "config pointer must not be null");
+  (*config).recovery_threshold()
+}
+
+/// Writes the indices of the systematic chunks to `indices_out`.
+///
+/// Holding all systematic chunks lets reconstruction skip the Reed-Solomon
+/// decode, so fetch logic should prefer the validators holding them.
+/// Returns the number of indices, which is `erasure_recovery_threshold`;
+/// nothing is written if `indices_out_len` is smaller than that.
+///
+/// # Safety
+/// - `config` must be a valid pointer returned by new_erasure_config
+/// - `indices_out` must point to `indices_out_len` writable indices
+#[no_mangle]
+pub unsafe extern "C" fn erasure_systematic_indices(
+  config: *const ErasureConfig,
+  indices_out: *mut u16,
+  indices_out_len: size_t,
+) -> size_t {
+  debug_assert!(!config.is_null(), "config pointer must not be null");
+  let indices = (*config).systematic_indices();
+  let count = indices.len();
+
+  if !indices_out.is_null() && indices_out_len >= count {
+    for (i, index) in indices.enumerate() {
+      *indices_out.add(i) = index;
+    }
+  }
+
+  count
+}
+
+/// Returns whether chunk `index` is a systematic data chunk.
+///
+/// # Safety
+/// - `config` must be a valid pointer returned by new_erasure_config
+#[no_mangle]
+pub unsafe extern "C" fn erasure_is_systematic(
+  config: *const ErasureConfig,
+  index: u16,
+) -> bool {
+  debug_assert!(!config.is_null(), "config pointer must not be null");
+  (*config).is_systematic(index)
+}
+
+/// Returns the erasure-coded piece size W_E in octets.
+///
+/// # Safety
+/// - `config` must be a valid pointer returned by new_erasure_config
+#[no_mangle]
+pub unsafe extern "C" fn erasure_piece_size(
+  config: *const ErasureConfig,
+) -> size_t {
+  debug_assert!(!config.is_null(), "config pointer must not be null");
+  (*config).piece_size()
+}
+
+/// Returns the size in bytes of each chunk produced for `data_len` bytes.
+///
+/// # Safety
+/// - `config` must be a valid pointer returned by new_erasure_config
+#[no_mangle]
+pub unsafe extern "C" fn erasure_chunk_len(
+  config: *const ErasureConfig,
+  data_len: size_t,
+) -> size_t {
+  debug_assert!(!config.is_null(), "config pointer must not be null");
+  (*config).chunk_len(data_len)
+}
+
+/// Erasure-codes a data blob into one chunk per validator.
+///
+/// `chunks_out` receives all chunks back to back and must be
+/// `chunks_out_len` bytes long, which must equal
+/// `erasure_chunk_count(config) * erasure_chunk_len(config, data_len)`.
+///
+/// # Safety
+/// - `config` must be a valid pointer returned by new_erasure_config
+/// - `data` must point to `data_len` readable bytes
+/// - `chunks_out` must point to `chunks_out_len` writable bytes
+#[no_mangle]
+pub unsafe extern "C" fn erasure_encode(
+  config: *const ErasureConfig,
+  data: *const u8,
+  data_len: size_t,
+  chunks_out: *mut u8,
+  chunks_out_len: size_t,
+) -> c_int {
+  if config.is_null() || data.is_null() || chunks_out.is_null() {
+    return ERASURE_ERROR;
+  }
+
+  let config = &*config;
+  let chunk_len = config.chunk_len(data_len);
+  if chunks_out_len != config.chunk_count() as usize * chunk_len {
+    return ERASURE_ERROR;
+  }
+
+  let data = slice::from_raw_parts(data, data_len);
+  let chunks = match codec::encode(config, data) {
+    Ok(chunks) => chunks,
+    Err(e) => return status_code(&e),
+  };
+
+  for (i, chunk) in chunks.iter().enumerate() {
+    ptr::copy_nonoverlapping(
+      chunk.as_ptr(),
+      chunks_out.add(i * chunk_len),
+      chunk_len,
+    );
+  }
+
+  ERASURE_OK
+}
+
+/// Erasure-codes several independent blobs in one call.
+///
+/// `blobs` and `blob_lens` describe `blobs_count` input blobs. The chunks of
+/// blob `b` are written back to back to `chunks_out[b]`, whose length
+/// `chunks_out_lens[b]` must equal `erasure_chunk_count(config) *
+/// erasure_chunk_len(config, blob_lens[b])`.
+///
+/// Blobs are spread over at most `max_threads` worker threads, or one per
+/// available CPU when `max_threads` is `0`.
+///
+/// # Safety
+/// - `config` must be a valid pointer returned by new_erasure_config
+/// - `blobs`, `blob_lens`, `chunks_out` and `chunks_out_lens` must each
+///   point to `blobs_count` readable entries
+/// - `blobs[b]` must point to `blob_lens[b]` readable bytes and
+///   `chunks_out[b]` to `chunks_out_lens[b]` writable bytes
+#[no_mangle]
+pub unsafe extern "C" fn erasure_encode_batch(
+  config: *const ErasureConfig,
+  blobs: *const *const u8,
+  blob_lens: *const size_t,
+  blobs_count: size_t,
+  max_threads: size_t,
+  chunks_out: *const *mut u8,
+  chunks_out_lens: *const size_t,
+) -> c_int {
+  if config.is_null()
+    || blobs.is_null()
+    || blob_lens.is_null()
+    || chunks_out.is_null()
+    || chunks_out_lens.is_null()
+  {
+    return ERASURE_ERROR;
+  }
+
+  let config = &*config;
+  let n_chunks = config.chunk_count() as usize;
+  let blob_ptrs = slice::from_raw_parts(blobs, blobs_count);
+  let blob_lens = slice::from_raw_parts(blob_lens, blobs_count);
+  let out_ptrs = slice::from_raw_parts(chunks_out, blobs_count);
+  let out_lens = slice::from_raw_parts(chunks_out_lens, blobs_count);
+
+  let mut inputs = Vec::with_capacity(blobs_count);
+  for b in 0..blobs_count {
+    if blob_ptrs[b].is_null()
+      || out_ptrs[b].is_null()
+      || out_lens[b] != n_chunks * config.chunk_len(blob_lens[b])
+    {
+      return ERASURE_ERROR;
+    }
+    inputs.push(slice::from_raw_parts(blob_ptrs[b], blob_lens[b]));
+  }
+
+  let encoded = match batch::encode_batch(config, &inputs, max_threads) {
+    Ok(encoded) => encoded,
+    Err(e) => return status_code(&e),
+  };
+
+  for (b, chunks) in encoded.iter().enumerate() {
+    let chunk_len = config.chunk_len(blob_lens[b]);
+    for (i, chunk) in chunks.iter().enumerate() {
+      ptr::copy_nonoverlapping(
+        chunk.as_ptr(),
+        out_ptrs[b].add(i * chunk_len),
+        chunk_len,
+      );
+    }
+  }
+
+  ERASURE_OK
+}
+
+/// Reconstructs the original data from a subset of chunks.
+///
+/// `chunks` holds `chunks_count` chunks back to back, each
+/// `erasure_chunk_len(config, data_len)` bytes long, and `chunk_indices`
+/// holds the validator index of each of them. `data_out` receives the
+/// original `data_len` bytes.
+///
+/// When all systematic chunks are supplied the data is rebuilt from them
+/// directly, without a Reed-Solomon decode.
+///
+/// Returns `ERASURE_NOT_ENOUGH_CHUNKS` if fewer than
+/// `erasure_recovery_threshold(config)` distinct chunks are supplied and
+/// `ERASURE_INCONSISTENT_CHUNKS` if an index is out of range or repeated.
+///
+/// # Safety
+/// - `config` must be a valid pointer returned by new_erasure_config
+/// - `chunks` must point to `chunks_count * chunk_len` readable bytes
+/// - `chunk_indices` must point to `chunks_count` readable indices
+/// - `data_out` must point to `data_len` writable bytes
+#[no_mangle]
+pub unsafe extern "C" fn erasure_reconstruct(
+  config: *const ErasureConfig,
+  chunks: *const u8,
+  chunk_indices: *const u16,
+  chunks_count: size_t,
+  data_len: size_t,
+  data_out: *mut u8,
+) -> c_int {
+  if config.is_null()
+    || chunks.is_null()
+    || chunk_indices.is_null()
+    || data_out.is_null()
+    || data_len == 0
+  {
+    return ERASURE_ERROR;
+  }
+
+  let config = &*config;
+  let chunk_len = config.chunk_len(data_len);
+
+  let chunks = slice::from_raw_parts(chunks, chunks_count * chunk_len);
+  let indices = slice::from_raw_parts(chunk_indices, chunks_count);
+  let supplied = indices.iter().copied().zip(chunks.chunks_exact(chunk_len));
+
+  match codec::reconstruct(config, supplied, data_len) {
+    Ok(data) => {
+      ptr::copy_nonoverlapping(data.as_ptr(), data_out, data_len);
+      ERASURE_OK
+    }
+    Err(e) => status_code(&e),
+  }
+}
+
+/// Returns the size of the largest justification produced for a bundle
+/// with `segments_count` exported segments.
+///
+/// This is the stride of `justifications_out` in `erasure_encode_with_root`.
+///
+/// # Safety
+/// - `config` must be a valid pointer returned by new_erasure_config
+#[no_mangle]
+pub unsafe extern "C" fn erasure_justification_max_len(
+  config: *const ErasureConfig,
+  segments_count: size_t,
+) -> size_t {
+  debug_assert!(!config.is_null(), "config pointer must not be null");
+  let config = &*config;
+  merkle::max_justification_len(
+    config.chunk_count() as usize,
+    erasure_root::leaf_len(config, segments_count),
+  )
+}
+
+/// Erasure-codes a bundle and its exported segments and computes the
+/// erasure root together with every validator's justification.
+///
+/// Outputs, for `n = erasure_chunk_count(config)` validators:
+/// - `chunks_out`: `n` bundle chunks of `erasure_chunk_len(config,
+///   data_len)` bytes each
+/// - `segment_chunks_out`: for every validator, its chunk of each segment in
+///   segment order, `segments_count * erasure_chunk_len(config, 4104)` bytes
+///   per validator
+/// - `root_out`: the 32-byte erasure root
+/// - `justifications_out`: `n` slots of
+///   `erasure_justification_max_len(config, segments_count)` bytes, slot `i`
+///   holding the justification of validator `i`
+/// - `justification_lens_out`: `n` entries with the used length of each slot
+///
+/// `segments` holds the exported segments followed by their paged proofs
+/// P(s), which the caller builds from the constant-depth segment tree; the
+/// root only matches the work report if the paged proofs are included.
+/// `segments` may be null when `segments_count` is zero.
+///
+/// # Safety
+/// - `config` must be a valid pointer returned by new_erasure_config
+/// - `data` must point to `data_len` readable bytes
+/// - `segments` must point to `segments_count * 4104` readable bytes
+/// - all output pointers must point to writable buffers of the sizes above,
+///   with `chunks_out_len`, `segment_chunks_out_len` and
+///   `justifications_out_len` giving the byte lengths of their buffers
+#[no_mangle]
+pub unsafe extern "C" fn erasure_encode_with_root(
+  config: *const ErasureConfig,
+  data: *const u8,
+  data_len: size_t,
+  segments: *const u8,
+  segments_count: size_t,
+  chunks_out: *mut u8,
+  chunks_out_len: size_t,
+  segment_chunks_out: *mut u8,
+  segment_chunks_out_len: size_t,
+  root_out: *mut u8,
+  justifications_out: *mut u8,
+  justifications_out_len: size_t,
+  justification_lens_out: *mut size_t,
+) -> c_int {
+  if config.is_null()
+    || data.is_null()
+    || (segments.is_null() && segments_count > 0)
+    || chunks_out.is_null()
+    || (segment_chunks_out.is_null() && segments_count > 0)
+    || root_out.is_null()
+    || justifications_out.is_null()
+    || justification_lens_out.is_null()
+  {
+    return ERASURE_ERROR;
+  }
+
+  let config = &*config;
+  let n_chunks = config.chunk_count() as usize;
+  let chunk_len = config.chunk_len(data_len);
+  let segment_chunks_len = segments_count * segment::segment_chunk_len(config);
+  let stride = erasure_justification_max_len(config, segments_count);
+
+  if chunks_out_len != n_chunks * chunk_len
+    || segment_chunks_out_len != n_chunks * segment_chunks_len
+    || justifications_out_len != n_chunks * stride
+  {
+    return ERASURE_ERROR;
+  }
+
+  let data = slice::from_raw_parts(data, data_len);
+  let segments: Vec<&[u8]> = if segments_count == 0 {
+    Vec::new()
+  } else {
+    slice::from_raw_parts(segments, segments_count * SEGMENT_SIZE)
+      .chunks_exact(SEGMENT_SIZE)
+      .collect()
+  };
+
+  let encoded = match erasure_root::encode_with_root(config, data, &segments) {
+    Ok(encoded) => encoded,
+    Err(e) => return status_code(&e),
+  };
+
+  for i in 0..n_chunks {
+    ptr::copy_nonoverlapping(
+      encoded.bundle_chunks[i].as_ptr(),
+      chunks_out.add(i * chunk_len),
+      chunk_len,
+    );
+    if segment_chunks_len > 0 {
+      ptr::copy_nonoverlapping(
+        encoded.segment_chunks[i].as_ptr(),
+        segment_chunks_out.add(i * segment_chunks_len),
+        segment_chunks_len,
+      );
+    }
+
+    let justification = &encoded.justifications[i];
+    ptr::copy_nonoverlapping(
+      justification.as_ptr(),
+      justifications_out.add(i * stride),
+      justification.len(),
+    );
+    *justification_lens_out.add(i) = justification.len();
+  }
+
+  ptr::copy_nonoverlapping(
+    encoded.erasure_root.as_ptr(),
+    root_out,
+    HASH_LENGTH,
+  );
+
+  ERASURE_OK
+}
+
+/// Verifies a validator's chunks against an erasure root.
+///
+/// `segment_chunks` holds the validator's chunk of every exported segment in
+/// segment order, as written by `erasure_encode_with_root`, and may be null
+/// when `segment_chunks_len` is zero. `justification` is the validator's
+/// justification and `root` the 32-byte erasure root from the work report.
+///
+/// Returns `ERASURE_OK` if the chunks belong at `index` under `root` and
+/// `ERASURE_INVALID_JUSTIFICATION` otherwise.
+///
+/// # Safety
+/// - `config` must be a valid pointer returned by new_erasure_config
+/// - `chunk`, `segment_chunks` and `justification` must point to readable
+///   buffers of their respective lengths
+/// - `root` must point to 32 readable bytes
+#[no_mangle]
+pub unsafe extern "C" fn erasure_verify_chunk(
+  config: *const ErasureConfig,
+  index: u16,
+  chunk: *const u8,
+  chunk_len: size_t,
+  segment_chunks: *const u8,
+  segment_chunks_len: size_t,
+  justification: *const u8,
+  justification_len: size_t,
+  root: *const u8,
+) -> c_int {
+  if config.is_null()
+    || chunk.is_null()
+    || (segment_chunks.is_null() && segment_chunks_len > 0)
+    || (justification.is_null() && justification_len > 0)
+    || root.is_null()
+  {
+    return ERASURE_ERROR;
+  }
+
+  let chunk = slice::from_raw_parts(chunk, chunk_len);
+  let segment_chunks = if segment_chunks_len == 0 {
+    &[]
+  } else {
+    slice::from_raw_parts(segment_chunks, segment_chunks_len)
+  };
+  let justification = if justification_len == 0 {
+    &[]
+  } else {
+    slice::from_raw_parts(justification, justification_len)
+  };
+  let root: [u8; HASH_LENGTH] =
+    match slice::from_raw_parts(root, HASH_LENGTH).try_into() {
+      Ok(root) => root,
+      Err(_) => return ERASURE_ERROR,
+    };
+
+  if erasure_root::verify_chunk(
+    &*config,
+    index,
+    chunk,
+    segment_chunks,
+    justification,
+    &root,
+  ) {
+    ERASURE_OK
+  } else {
+    ERASURE_INVALID_JUSTIFICATION
+  }
+}
+
+/// Returns the size in bytes of one validator's chunk of one segment.
+///
+/// # Safety
+/// - `config` must be a valid pointer returned by new_erasure_config
+#[no_mangle]
+pub unsafe extern "C" fn erasure_segment_chunk_len(
+  config: *const ErasureConfig,
+) -> size_t {
+  debug_assert!(!config.is_null(), "config pointer must not be null");
+  segment::segment_chunk_len(&*config)
+}
+
+/// Erasure-codes a batch of exported segments.
+///
+/// `segments` holds `segments_count` segments of 4104 bytes back to back.
+/// `segment_chunks_out` receives, for every validator in turn, its chunk of
+/// each segment in segment order, so it must be
+/// `erasure_chunk_count(config) * segments_count *
+/// erasure_segment_chunk_len(config)` bytes long.
+///
+/// # Safety
+/// - `config` must be a valid pointer returned by new_erasure_config
+/// - `segments` must point to `segments_count * 4104` readable bytes
+/// - `segment_chunks_out` must point to `segment_chunks_out_len` writable
+///   bytes
+#[no_mangle]
+pub unsafe extern "C" fn erasure_encode_segments(
+  config: *const ErasureConfig,
+  segments: *const u8,
+  segments_count: size_t,
+  segment_chunks_out: *mut u8,
+  segment_chunks_out_len: size_t,
+) -> c_int {
+  if config.is_null()
+    || segments.is_null()
+    || segment_chunks_out.is_null()
+    || segments_count == 0
+  {
+    return ERASURE_ERROR;
+  }
+
+  let config = &*config;
+  let per_validator_len = segments_count * segment::segment_chunk_len(config);
+  if segment_chunks_out_len != config.chunk_count() as usize * per_validator_len
+  {
+    return ERASURE_ERROR;
+  }
+
+  let segments: Vec<&[u8]> =
+    slice::from_raw_parts(segments, segments_count * SEGMENT_SIZE)
+      .chunks_exact(SEGMENT_SIZE)
+      .collect();
+
+  let segment_chunks = match segment::encode_segments(config, &segments) {
+    Ok(chunks) => chunks,
+    Err(e) => return status_code(&e),
+  };
+
+  for (i, chunks) in segment_chunks.iter().enumerate() {
+    ptr::copy_nonoverlapping(
+      chunks.as_ptr(),
+      segment_chunks_out.add(i * per_validator_len),
+      per_validator_len,
+    );
+  }
+
+  ERASURE_OK
+}
+
+/// Reconstructs selected segments of a batch from validators' segment
+/// chunks.
+///
+/// `segment_chunks` holds, for `chunks_count` validators, each validator's
+/// chunks of all `segments_count` segments as written by
+/// `erasure_encode_segments`, and `chunk_indices` holds the validator index
+/// of each. The segments listed in `segment_indices` are written to
+/// `segments_out` in that order, 4104 bytes each.
+///
+/// Returns `ERASURE_NOT_ENOUGH_CHUNKS` or `ERASURE_INCONSISTENT_CHUNKS` as
+/// `erasure_reconstruct` does.
+///
+/// # Safety
+/// - `config` must be a valid pointer returned by new_erasure_config
+/// - `segment_chunks` must point to `chunks_count * segments_count *
+///   erasure_segment_chunk_len(config)` readable bytes
+/// - `chunk_indices` must point to `chunks_count` readable indices
+/// - `segment_indices` must point to `segment_indices_count` readable indices
+/// - `segments_out` must point to `segment_indices_count * 4104` writable
+///   bytes
+#[no_mangle]
+pub unsafe extern "C" fn erasure_reconstruct_segments(
+  config: *const ErasureConfig,
+  segment_chunks: *const u8,
+  chunk_indices: *const u16,
+  chunks_count: size_t,
+  segments_count: size_t,
+  segment_indices: *const size_t,
+  segment_indices_count: size_t,
+  segments_out: *mut u8,
+) -> c_int {
+  if config.is_null()
+    || segment_chunks.is_null()
+    || chunk_indices.is_null()
+    || segment_indices.is_null()
+    || segments_out.is_null()
+  {
+    return ERASURE_ERROR;
+  }
+
+  let config = &*config;
+  let per_validator_len = segments_count * segment::segment_chunk_len(config);
+  if per_validator_len == 0 {
+    return ERASURE_ERROR;
+  }
+
+  let segment_chunks =
+    slice::from_raw_parts(segment_chunks, chunks_count * per_validator_len);
+  let indices = slice::from_raw_parts(chunk_indices, chunks_count);
+  let validator_chunks: Vec<(u16, &[u8])> = indices
+    .iter()
+    .copied()
+    .zip(segment_chunks.chunks_exact(per_validator_len))
+    .collect();
+  let wanted = slice::from_raw_parts(segment_indices, segment_indices_count);
+
+  let segments = match segment::reconstruct_segments(
+    config,
+    &validator_chunks,
+    segments_count,
+    wanted,
+  ) {
+    Ok(segments) => segments,
+    Err(e) => return status_code(&e),
+  };
+
+  for (i, segment) in segments.iter().enumerate() {
+    ptr::copy_nonoverlapping(
+      segment.as_ptr(),
+      segments_out.add(i * SEGMENT_SIZE),
+      SEGMENT_SIZE,
+    );
+  }
+
+  ERASURE_OK
+}
+
+fn store_status_code(error: &StoreError) -> c_int {
+  match error {
+    StoreError::Codec(e) => status_code(e),
+    StoreError::Io(_) | StoreError::CorruptTimeslot(_) => ERASURE_IO_ERROR,
+  }
+}
+
+unsafe fn read_root(root: *const u8) -> [u8; HASH_LENGTH] {
+  let mut out = [0u8; HASH_LENGTH];
+  ptr::copy_nonoverlapping(root, out.as_mut_ptr(), HASH_LENGTH);
+  out
+}
+
+/// Open the availability chunk store in a directory.
+///
+/// The directory is created if it does not exist. Returns null if `dir` is
+/// not valid UTF-8 or the directory cannot be created.
+///
+/// # Safety
+/// - `dir` must point to a NUL-terminated path
+#[no_mangle]
+pub unsafe extern "C" fn new_chunk_store(
+  dir: *const c_char,
+) -> *mut ChunkStore {
+  if dir.is_null() {
+    return ptr::null_mut();
+  }
+
+  let dir = match CStr::from_ptr(dir).to_str() {
+    Ok(dir) => dir,
+    Err(_) => return ptr::null_mut(),
+  };
+
+  match ChunkStore::open(dir) {
+    Ok(store) => Box::into_raw(Box::new(store)),
+    Err(_) => ptr::null_mut(),
+  }
+}
+
+/// Free an availability chunk store. Stored chunks remain on disk.
+///
+/// # Safety
+/// - `store` must be a valid pointer returned by new_chunk_store
+#[no_mangle]
+pub unsafe extern "C" fn free_chunk_store(store: *mut ChunkStore) {
+  debug_assert!(!store.is_null(), "store pointer must not be null");
+  drop(Box::from_raw(store));
+}
+
+/// Store the chunk of validator `index` under a 32-byte erasure root.
+///
+/// `timeslot` is the slot the chunk belongs to and drives expiry.
+///
+/// # Safety
+/// - `store` must be a valid pointer returned by new_chunk_store
+/// - `root` must point to 32 readable bytes
+/// - `chunk` must point to `chunk_len` readable bytes
+#[no_mangle]
+pub unsafe extern "C" fn chunk_store_put(
+  store: *const ChunkStore,
+  root: *const u8,
+  index: u16,
+  chunk: *const u8,
+  chunk_len: size_t,
+  timeslot: u32,
+) -> c_int {
+  if store.is_null() || root.is_null() || chunk.is_null() {
+    return ERASURE_ERROR;
+  }
+
+  let chunk = slice::from_raw_parts(chunk, chunk_len);
+  match (*store).put(&read_root(root), index, chunk, timeslot) {
+    Ok(()) => ERASURE_OK,
+    Err(e) => store_status_code(&e),
+  }
+}
+
+/// Load the chunk of validator `index` stored under a 32-byte erasure root.
+///
+/// The chunk length is written to `chunk_len_out` and the chunk itself to
+/// `chunk_out` if it fits in `chunk_out_len` bytes; otherwise
+/// `ERASURE_ERROR` is returned and the caller can retry with a larger
+/// buffer. Returns `ERASURE_NOT_FOUND` if no such chunk is stored.
+///
+/// # Safety
+/// - `store` must be a valid pointer returned by new_chunk_store
+/// - `root` must point to 32 readable bytes
+/// - `chunk_out` must point to `chunk_out_len` writable bytes
+/// - `chunk_len_out` must point to a writable `size_t`
+#[no_mangle]
+pub unsafe extern "C" fn chunk_store_get(
+  store: *const ChunkStore,
+  root: *const u8,
+  index: u16,
+  chunk_out: *mut u8,
+  chunk_out_len: size_t,
+  chunk_len_out: *mut size_t,
+) -> c_int {
+  if store.is_null()
+    || root.is_null()
+    || chunk_out.is_null()
+    || chunk_len_out.is_null()
+  {
+    return ERASURE_ERROR;
+  }
+
+  let chunk = match (*store).get(&read_root(root), index) {
+    Ok(Some(chunk)) => chunk,
+    Ok(None) => return ERASURE_NOT_FOUND,
+    Err(e) => return store_status_code(&e),
+  };
+
+  *chunk_len_out = chunk.len();
+  if chunk.len() > chunk_out_len {
+    return ERASURE_ERROR;
+  }
+  ptr::copy_nonoverlapping(chunk.as_ptr(), chunk_out, chunk.len());
+
+  ERASURE_OK
+}
+
+/// Remove every erasure root whose latest timeslot is before
+/// `before_timeslot`.
+///
+/// The number of removed roots is written to `removed_out`.
+///
+/// # Safety
+/// - `store` must be a valid pointer returned by new_chunk_store
+/// - `removed_out` must point to a writable `size_t`
+#[no_mangle]
+pub unsafe extern "C" fn chunk_store_expire(
+  store: *const ChunkStore,
+  before_timeslot: u32,
+  removed_out: *mut size_t,
+) -> c_int {
+  if store.is_null() || removed_out.is_null() {
+    return ERASURE_ERROR;
+  }
+
+  match (*store).expire(before_timeslot) {
+    Ok(removed) => {
+      *removed_out = removed;
+      ERASURE_OK
+    }
+    Err(e) => store_status_code(&e),
+  }
+}
+
+/// Reconstruct the data committed to by a 32-byte erasure root from the
+/// chunks stored locally.
+///
+/// Writes the original `data_len` bytes to `data_out`. Returns
+/// `ERASURE_NOT_ENOUGH_CHUNKS` if fewer than
+/// `erasure_recovery_threshold(config)` chunks are stored, in which case the
+/// missing chunks have to be fetched from other validators first.
+///
+/// # Safety
+/// - `store` must be a valid pointer returned by new_chunk_store
+/// - `config` must be a valid pointer returned by new_erasure_config
+/// - `root` must point to 32 readable bytes
+/// - `data_out` must point to `data_len` writable bytes
+#[no_mangle]
+pub unsafe extern "C" fn chunk_store_reconstruct(
+  store: *const ChunkStore,
+  config: *const ErasureConfig,
+  root: *const u8,
+  data_len: size_t,
+  data_out: *mut u8,
+) -> c_int {
+  if store.is_null() || config.is_null() || root.is_null() || data_out.is_null()
+  {
+    return ERASURE_ERROR;
+  }
+
+  match (*store).reconstruct(&*config, &read_root(root), data_len) {
+    Ok(Some(data)) => {
+      ptr::copy_nonoverlapping(data.as_ptr(), data_out, data_len);
+      ERASURE_OK
+    }
+    Ok(None) => ERASURE_NOT_ENOUGH_CHUNKS,
+    Err(e) => store_status_code(&e),
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::config::TINY_VALIDATORS_COUNT;
+
+  fn tiny_config() -> *mut ErasureConfig {
+    let config = new_erasure_config(TINY_VALIDATORS_COUNT);
+    assert!(!config.is_null());
+    config
+  }
+
+  #[test]
+  fn test_new_erasure_config_rejects_invalid_params() {
+    assert!(new_erasure_config(1).is_null());
+    assert!(new_erasure_config(30).is_null());
+  }
+
+  #[test]
+  fn test_erasure_encode_writes_all_chunks() {
+    let config = tiny_config();
+    let data = b"jam erasure coding over the c abi";
+
+    unsafe {
+      let n_chunks = erasure_chunk_count(config) as usize;
+      let chunk_len = erasure_chunk_len(config, data.len());
+      let mut out = vec![0u8; n_chunks * chunk_len];
+
+      let result = erasure_encode(
+        config,
+        data.as_ptr(),
+        data.len(),
+        out.as_mut_ptr(),
+        out.len(),
+      );
+      assert_eq!(result, ERASURE_OK);
+
+      let expected = codec::encode(&*config, data).unwrap().concat();
+      assert_eq!(out, expected);
+
+      free_erasure_config(config);
+    }
+  }
+
+  #[test]
+  fn test_erasure_encode_rejects_wrong_buffer_size() {
+    let config = tiny_config();
+    let data = [1u8; 16];
+    let mut out = vec![0u8; 3];
+
+    unsafe {
+      let result = erasure_encode(
+        config,
+        data.as_ptr(),
+        data.len(),
+        out.as_mut_ptr(),
+        out.len(),
+      );
+      assert_eq!(result, ERASURE_ERROR);
+
+      free_erasure_config(config);
+    }
+  }
+
+  #[test]
+  fn test_erasure_reconstruct_roundtrip() {
+    let config = tiny_config();
+    let data = b"work-package bundle rebuilt by an auditor";
+
+    unsafe {
+      let chunks = codec::encode(&*config, data).unwrap();
+
+      let indices = [5u16, 2];
+      let supplied = [chunks[5].as_slice(), chunks[2].as_slice()].concat();
+      let mut out = vec![0u8; data.len()];
+
+      let result = erasure_reconstruct(
+        config,
+        supplied.as_ptr(),
+        indices.as_ptr(),
+        indices.len(),
+        data.len(),
+        out.as_mut_ptr(),
+      );
+      assert_eq!(result, ERASURE_OK);
+      assert_eq!(out, data);
+
+      free_erasure_config(config);
+    }
+  }
+
+  #[test]
+  fn test_erasure_reconstruct_reports_too_few_chunks() {
+    let config = tiny_config();
+    let data = [7u8; 64];
+
+    unsafe {
+      let chunks = codec::encode(&*config, &data).unwrap();
+
+      let indices = [4u16];
+      let mut out = vec![0u8; data.len()];
+
+      let result = erasure_reconstruct(
+        config,
+        chunks[4].as_ptr(),
+        indices.as_ptr(),
+        indices.len(),
+        data.len(),
+        out.as_mut_ptr(),
+      );
+      assert_eq!(result, ERASURE_NOT_ENOUGH_CHUNKS);
+
+      free_erasure_config(config);
+    }
+  }
+
+  #[test]
+  fn test_erasure_encode_with_root() {
+    let config = tiny_config();
+    let data = [9u8; 300];
+    let segments = vec![5u8; 2 * SEGMENT_SIZE];
+
+    unsafe {
+      let n = erasure_chunk_count(config) as usize;
+      let chunk_len = erasure_chunk_len(config, data.len());
+      let segment_chunks_len = 2 * erasure_chunk_len(config, SEGMENT_SIZE);
+      let stride = erasure_justification_max_len(config, 2);
+
+      let mut chunks = vec![0u8; n * chunk_len];
+      let mut segment_chunks = vec![0u8; n * segment_chunks_len];
+      let mut root = [0u8; HASH_LENGTH];
+      let mut justifications = vec![0u8; n * stride];
+      let mut justification_lens = vec![0usize; n];
+
+      let result = erasure_encode_with_root(
+        config,
+        data.as_ptr(),
+        data.len(),
+        segments.as_ptr(),
+        2,
+        chunks.as_mut_ptr(),
+        chunks.len(),
+        segment_chunks.as_mut_ptr(),
+        segment_chunks.len(),
+        root.as_mut_ptr(),
+        justifications.as_mut_ptr(),
+        justifications.len(),
+        justification_lens.as_mut_ptr(),
+      );
+      assert_eq!(result, ERASURE_OK);
+
+      let segment_refs: Vec<&[u8]> =
+        segments.chunks_exact(SEGMENT_SIZE).collect();
+      let expected =
+        erasure_root::encode_with_root(&*config, &data, &segment_refs).unwrap();
+
+      assert_eq!(root, expected.erasure_root);
+      assert_eq!(chunks, expected.bundle_chunks.concat());
+      assert_eq!(segment_chunks, expected.segment_chunks.concat());
+      for (i, justification) in expected.justifications.iter().enumerate() {
+        assert_eq!(justification_lens[i], justification.len());
+        assert_eq!(
+          &justifications[i * stride..i * stride + justification.len()],
+          &justification[..]
+        );
+      }
+
+      free_erasure_config(config);
+    }
+  }
+
+  #[test]
+  fn test_erasure_verify_chunk() {
+    let config = tiny_config();
+
+    let data: Vec<u8> = (0..77u8).collect();
+
+    unsafe {
+      let encoded =
+        erasure_root::encode_with_root(&*config, &data, &[]).unwrap();
+      let root = encoded.erasure_root;
+      let chunk = &encoded.bundle_chunks[3];
+      let justification = &encoded.justifications[3];
+
+      let result = erasure_verify_chunk(
+        config,
+        3,
+        chunk.as_ptr(),
+        chunk.len(),
+        ptr::null(),
+        0,
+        justification.as_ptr(),
+        justification.len(),
+        root.as_ptr(),
+      );
+      assert_eq!(result, ERASURE_OK);
+
+      let result = erasure_verify_chunk(
+        config,
+        4,
+        chunk.as_ptr(),
+        chunk.len(),
+        ptr::null(),
+        0,
+        justification.as_ptr(),
+        justification.len(),
+        root.as_ptr(),
+      );
+      assert_eq!(result, ERASURE_INVALID_JUSTIFICATION);
+
+      free_erasure_config(config);
+    }
+  }
+
+  #[test]
+  fn test_erasure_segments_roundtrip() {
+    let config = tiny_config();
+    let segments: Vec<u8> =
+      (0..2 * SEGMENT_SIZE).map(|i| (i % 251) as u8).collect();
+
+    unsafe {
+      let n = erasure_chunk_count(config) as usize;
+      let per_validator_len = 2 * erasure_segment_chunk_len(config);
+      let mut segment_chunks = vec![0u8; n * per_validator_len];
+
+      let result = erasure_encode_segments(
+        config,
+        segments.as_ptr(),
+        2,
+        segment_chunks.as_mut_ptr(),
+        segment_chunks.len(),
+      );
+      assert_eq!(result, ERASURE_OK);
+
+      // Rebuild the second segment from validators 1 and 4
+      let indices = [1u16, 4];
+      let supplied = [
+        &segment_chunks[per_validator_len..2 * per_validator_len],
+        &segment_chunks[4 * per_validator_len..5 * per_validator_len],
+      ]
+      .concat();
+      let wanted = [1usize];
+      let mut out = vec![0u8; SEGMENT_SIZE];
+
+      let result = erasure_reconstruct_segments(
+        config,
+        supplied.as_ptr(),
+        indices.as_ptr(),
+        indices.len(),
+        2,
+        wanted.as_ptr(),
+        wanted.len(),
+        out.as_mut_ptr(),
+      );
+      assert_eq!(result, ERASURE_OK);
+      assert_eq!(out, &segments[SEGMENT_SIZE..]);
+
+      free_erasure_config(config);
+    }
+  }
+
+  #[test]
+  fn test_erasure_encode_batch() {
+    let config = tiny_config();
+    let first = b"first work-package bundle".to_vec();
+    let second = vec![0xab; 1500];
+
+    unsafe {
+      let n = erasure_chunk_count(config) as usize;
+      let mut first_out = vec![0u8; n * erasure_chunk_len(config, first.len())];
+      let mut second_out =
+        vec![0u8; n * erasure_chunk_len(config, second.len())];
+
+      let blobs = [first.as_ptr(), second.as_ptr()];
+      let blob_lens = [first.len(), second.len()];
+      let outs = [first_out.as_mut_ptr(), second_out.as_mut_ptr()];
+      let out_lens = [first_out.len(), second_out.len()];
+
+      let result = erasure_encode_batch(
+        config,
+        blobs.as_ptr(),
+        blob_lens.as_ptr(),
+        2,
+        2,
+        outs.as_ptr(),
+        out_lens.as_ptr(),
+      );
+      assert_eq!(result, ERASURE_OK);
+
+      assert_eq!(first_out, codec::encode(&*config, &first).unwrap().concat());
+      assert_eq!(
+        second_out,
+        codec::encode(&*config, &second).unwrap().concat()
+      );
+
+      free_erasure_config(config);
+    }
+  }
+
+  #[test]
+  fn test_erasure_systematic_indices() {
+    let config = tiny_config();
+
+    unsafe {
+      let mut indices = [u16::MAX; 4];
+      let count =
+        erasure_systematic_indices(config, indices.as_mut_ptr(), indices.len());
+
+      assert_eq!(count, erasure_recovery_threshold(config));
+      assert_eq!(&indices[..count], &[0, 1]);
+      assert!(erasure_is_systematic(config, 0));
+      assert!(!erasure_is_systematic(config, 5));
+
+      free_erasure_config(config);
+    }
+  }
+
+  #[test]
+  fn test_chunk_store_roundtrip() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = std::ffi::CString::new(dir.path().to_str().unwrap()).unwrap();
+    let config = tiny_config();
+    let data: Vec<u8> = (0..90u8).collect();
+    let root = [0x11u8; HASH_LENGTH];
+
+    unsafe {
+      let store = new_chunk_store(path.as_ptr());
+      assert!(!store.is_null());
+
+      let chunks = codec::encode(&*config, &data).unwrap();
+      for index in [1u16, 5] {
+        let chunk = &chunks[index as usize];
+        let result = chunk_store_put(
+          store,
+          root.as_ptr(),
+          index,
+          chunk.as_ptr(),
+          chunk.len(),
+          42,
+        );
+        assert_eq!(result, ERASURE_OK);
+      }
+
+      let mut chunk = vec![0u8; chunks[1].len()];
+      let mut chunk_len = 0;
+      let result = chunk_store_get(
+        store,
+        root.as_ptr(),
+        1,
+        chunk.as_mut_ptr(),
+        chunk.len(),
+        &mut chunk_len,
+      );
+      assert_eq!(result, ERASURE_OK);
+      assert_eq!(chunk, chunks[1]);
+
+      let result = chunk_store_get(
+        store,
+        root.as_ptr(),
+        2,
+        chunk.as_mut_ptr(),
+        chunk.len(),
+        &mut chunk_len,
+      );
+      assert_eq!(result, ERASURE_NOT_FOUND);
+
+      let mut out = vec![0u8; data.len()];
+      let result = chunk_store_reconstruct(
+        store,
+        config,
+        root.as_ptr(),
+        data.len(),
+        out.as_mut_ptr(),
+      );
+      assert_eq!(result, ERASURE_OK);
+      assert_eq!(out, data);
+
+      let mut removed = 0;
+      assert_eq!(chunk_store_expire(store, 43, &mut removed), ERASURE_OK);
+      assert_eq!(removed, 1);
+
+      let result = chunk_store_reconstruct(
+        store,
+        config,
+        root.as_ptr(),
+        data.len(),
+        out.as_mut_ptr(),
+      );
+      assert_eq!(result, ERASURE_NOT_ENOUGH_CHUNKS);
+
+      free_chunk_store(store);
+      free_erasure_config(config);
+    }
+  }
+}
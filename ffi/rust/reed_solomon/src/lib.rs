@@ -1,2 +1,13 @@
 pub use erasure_coding;
 
+pub mod batch;
+pub mod codec;
+pub mod config;
+pub mod erasure_root;
+pub mod ffi;
+pub mod merkle;
+pub mod segment;
+pub mod store;
+
+#[cfg(test)]
+mod test_vectors;
@@ -0,0 +1,248 @@
+//! Well-balanced binary Merkle tree over Blake2b-256
+//!
+//! Implements M_B and its trace T from appendix E of the Gray Paper. Inner
+//! nodes are `H("node" ++ left ++ right)`, a subtree holding a single leaf
+//! is the leaf blob itself, and the root of a single-leaf tree is the hash
+//! of that leaf.
+//!
+//! A justification is the trace of a leaf, top-down. Entries for sibling
+//! subtrees with two or more leaves are 32-byte hashes and entries for
+//! single-leaf siblings are the raw sibling leaf, so the layout follows
+//! from the leaf count, the leaf index and the (uniform) leaf length.
+
+pub const HASH_LENGTH: usize = 32;
+
+pub type Hash = [u8; HASH_LENGTH];
+
+const NODE_PREFIX: &[u8] = b"node";
+
+/// Blake2b-256 of `data`.
+pub fn hash(data: &[u8]) -> Hash {
+  let digest = blake2b_simd::Params::new()
+    .hash_length(HASH_LENGTH)
+    .hash(data);
+  let mut out = [0u8; HASH_LENGTH];
+  out.copy_from_slice(digest.as_bytes());
+  out
+}
+
+fn node_hash(left: &[u8], right: &[u8]) -> Hash {
+  let digest = blake2b_simd::Params::new()
+    .hash_length(HASH_LENGTH)
+    .to_state()
+    .update(NODE_PREFIX)
+    .update(left)
+    .update(right)
+    .finalize();
+  let mut out = [0u8; HASH_LENGTH];
+  out.copy_from_slice(digest.as_bytes());
+  out
+}
+
+/// N(v, H), recording the sibling of every leaf on the way up.
+fn build(
+  leaves: &[Vec<u8>],
+  offset: usize,
+  traces: &mut [Vec<Vec<u8>>],
+) -> Vec<u8> {
+  match leaves.len() {
+    0 => [0u8; HASH_LENGTH].to_vec(),
+    1 => leaves[0].clone(),
+    n => {
+      let mid = n.div_ceil(2);
+      let left = build(&leaves[..mid], offset, traces);
+      let right = build(&leaves[mid..], offset + mid, traces);
+
+      for trace in &mut traces[offset..offset + mid] {
+        trace.push(right.clone());
+      }
+      for trace in &mut traces[offset + mid..offset + n] {
+        trace.push(left.clone());
+      }
+
+      node_hash(&left, &right).to_vec()
+    }
+  }
+}
+
+/// Position of a leaf at each level of the tree, top-down: whether it lies
+/// in the left half and whether its sibling subtree is a single leaf.
+fn path(leaf_count: usize, index: usize) -> Vec<(bool, bool)> {
+  let mut steps = Vec::new();
+  let (mut len, mut index) = (leaf_count, index);
+
+  while len > 1 {
+    let mid = len.div_ceil(2);
+    if index < mid {
+      steps.push((true, len - mid == 1));
+      len = mid;
+    } else {
+      steps.push((false, mid == 1));
+      index -= mid;
+      len -= mid;
+    }
+  }
+
+  steps
+}
+
+/// Computes the root of the tree and the justification of every leaf.
+pub fn root_with_justifications(leaves: &[Vec<u8>]) -> (Hash, Vec<Vec<u8>>) {
+  let mut traces = vec![Vec::new(); leaves.len()];
+  let top = build(leaves, 0, &mut traces);
+
+  let root = match leaves.len() {
+    1 => hash(&leaves[0]),
+    _ => top.try_into().expect("inner nodes are 32-byte hashes"),
+  };
+
+  let justifications = traces
+    .into_iter()
+    .map(|trace| trace.into_iter().rev().flatten().collect())
+    .collect();
+
+  (root, justifications)
+}
+
+/// Returns the length in bytes of the justification of leaf `index`.
+pub fn justification_len(
+  leaf_count: usize,
+  index: usize,
+  leaf_len: usize,
+) -> usize {
+  path(leaf_count, index)
+    .iter()
+    .map(|&(_, single)| if single { leaf_len } else { HASH_LENGTH })
+    .sum()
+}
+
+/// Returns the longest justification length over all leaves.
+pub fn max_justification_len(leaf_count: usize, leaf_len: usize) -> usize {
+  (0..leaf_count)
+    .map(|index| justification_len(leaf_count, index, leaf_len))
+    .max()
+    .unwrap_or(0)
+}
+
+/// Checks that `leaf` sits at `index` of a `leaf_count`-leaf tree with the
+/// given `root`, using its justification.
+pub fn verify(
+  leaf_count: usize,
+  index: usize,
+  leaf: &[u8],
+  justification: &[u8],
+  root: &Hash,
+) -> bool {
+  if index >= leaf_count {
+    return false;
+  }
+  if justification.len() != justification_len(leaf_count, index, leaf.len()) {
+    return false;
+  }
+  if leaf_count == 1 {
+    return hash(leaf) == *root;
+  }
+
+  // Split the top-down trace into its entries
+  let steps = path(leaf_count, index);
+  let mut siblings = Vec::with_capacity(steps.len());
+  let mut rest = justification;
+  for &(_, single) in &steps {
+    let len = if single { leaf.len() } else { HASH_LENGTH };
+    let (sibling, tail) = rest.split_at(len);
+    siblings.push(sibling);
+    rest = tail;
+  }
+
+  // Fold from the leaf back up to the root
+  let mut current = leaf.to_vec();
+  for (&(is_left, _), sibling) in steps.iter().zip(siblings).rev() {
+    let parent = if is_left {
+      node_hash(&current, sibling)
+    } else {
+      node_hash(sibling, &current)
+    };
+    current = parent.to_vec();
+  }
+
+  current == root[..]
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn leaves(count: usize) -> Vec<Vec<u8>> {
+    (0..count).map(|i| vec![i as u8; 40]).collect()
+  }
+
+  #[test]
+  fn test_single_leaf_root_is_leaf_hash() {
+    let leaves = leaves(1);
+    let (root, justifications) = root_with_justifications(&leaves);
+
+    assert_eq!(root, hash(&leaves[0]));
+    assert!(justifications[0].is_empty());
+  }
+
+  #[test]
+  fn test_three_leaf_tree() {
+    let leaves = leaves(3);
+    let (root, justifications) = root_with_justifications(&leaves);
+
+    // The left half holds two leaves, the right half is a single leaf
+    let left = node_hash(&leaves[0], &leaves[1]);
+    assert_eq!(root, node_hash(&left, &leaves[2]));
+
+    assert_eq!(justifications[0], [&leaves[2][..], &leaves[1]].concat());
+    assert_eq!(justifications[2], left.to_vec());
+  }
+
+  #[test]
+  fn test_justification_lengths_match_layout() {
+    for count in [2, 6, 7, 1023] {
+      let leaves = leaves(count);
+      let (_, justifications) = root_with_justifications(&leaves);
+
+      for (i, justification) in justifications.iter().enumerate() {
+        assert_eq!(justification.len(), justification_len(count, i, 40));
+      }
+    }
+  }
+
+  #[test]
+  fn test_verify_accepts_every_leaf() {
+    for count in [1, 2, 3, 6, 1023] {
+      let leaves = leaves(count);
+      let (root, justifications) = root_with_justifications(&leaves);
+
+      for (i, justification) in justifications.iter().enumerate() {
+        assert!(verify(count, i, &leaves[i], justification, &root));
+      }
+    }
+  }
+
+  #[test]
+  fn test_verify_rejects_tampering() {
+    let leaves = leaves(6);
+    let (root, justifications) = root_with_justifications(&leaves);
+
+    // Wrong index
+    assert!(!verify(6, 1, &leaves[2], &justifications[2], &root));
+    assert!(!verify(6, 6, &leaves[2], &justifications[2], &root));
+
+    // Modified leaf
+    let mut leaf = leaves[2].clone();
+    leaf[0] ^= 1;
+    assert!(!verify(6, 2, &leaf, &justifications[2], &root));
+
+    // Modified justification
+    let mut justification = justifications[2].clone();
+    justification[0] ^= 1;
+    assert!(!verify(6, 2, &leaves[2], &justification, &root));
+
+    // Truncated justification
+    let truncated = &justifications[2][1..];
+    assert!(!verify(6, 2, &leaves[2], truncated, &root));
+  }
+}
@@ -0,0 +1,161 @@
+//! Erasure coding of exported segments
+//!
+//! Each 4104-byte segment is coded on its own into one chunk per validator.
+//! A validator's segment chunks for a batch are stored back to back in
+//! segment order, so the chunk of segment `j` starts at
+//! `j * segment_chunk_len`.
+
+use crate::{
+  codec::{self, Error},
+  config::{ErasureConfig, SEGMENT_SIZE},
+};
+
+/// Returns the size in bytes of one validator's chunk of one segment.
+pub fn segment_chunk_len(config: &ErasureConfig) -> usize {
+  config.chunk_len(SEGMENT_SIZE)
+}
+
+/// Erasure-codes a batch of segments.
+///
+/// Returns one entry per validator holding its chunk of every segment in
+/// segment order.
+pub fn encode_segments(
+  config: &ErasureConfig,
+  segments: &[&[u8]],
+) -> Result<Vec<Vec<u8>>, Error> {
+  if segments.iter().any(|s| s.len() != SEGMENT_SIZE) {
+    return Err(Error::InvalidSegmentSize);
+  }
+
+  let n_chunks = config.chunk_count() as usize;
+  let mut segment_chunks =
+    vec![
+      Vec::with_capacity(segments.len() * segment_chunk_len(config));
+      n_chunks
+    ];
+
+  for segment in segments {
+    for (validator, chunk) in
+      codec::encode(config, segment)?.into_iter().enumerate()
+    {
+      segment_chunks[validator].extend_from_slice(&chunk);
+    }
+  }
+
+  Ok(segment_chunks)
+}
+
+/// Reconstructs a single segment from chunks of that segment.
+///
+/// `chunks` yields `(validator index, chunk)` pairs, each chunk being
+/// `segment_chunk_len` bytes.
+pub fn reconstruct_segment<'a>(
+  config: &ErasureConfig,
+  chunks: impl IntoIterator<Item = (u16, &'a [u8])>,
+) -> Result<Vec<u8>, Error> {
+  codec::reconstruct(config, chunks, SEGMENT_SIZE)
+}
+
+/// Reconstructs selected segments of a batch from validators' segment
+/// chunks.
+///
+/// `validator_chunks` pairs a validator index with that validator's chunks
+/// of all `segments_count` segments, as produced by `encode_segments`.
+/// Returns the segments listed in `segment_indices`, in that order.
+pub fn reconstruct_segments(
+  config: &ErasureConfig,
+  validator_chunks: &[(u16, &[u8])],
+  segments_count: usize,
+  segment_indices: &[usize],
+) -> Result<Vec<Vec<u8>>, Error> {
+  let chunk_len = segment_chunk_len(config);
+  let expected_len = segments_count * chunk_len;
+
+  if let Some(&(index, chunks)) = validator_chunks
+    .iter()
+    .find(|(_, chunks)| chunks.len() != expected_len)
+  {
+    return Err(Error::InconsistentChunkLength {
+      index,
+      len: chunks.len(),
+      expected: expected_len,
+    });
+  }
+
+  segment_indices
+    .iter()
+    .map(|&segment| {
+      if segment >= segments_count {
+        return Err(Error::SegmentIndexOutOfRange(segment));
+      }
+      let offset = segment * chunk_len;
+      let chunks = validator_chunks
+        .iter()
+        .map(|&(index, chunks)| (index, &chunks[offset..offset + chunk_len]));
+      reconstruct_segment(config, chunks)
+    })
+    .collect()
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn segments(count: usize) -> Vec<Vec<u8>> {
+    (0..count)
+      .map(|s| (0..SEGMENT_SIZE).map(|i| (i * 31 + s) as u8).collect())
+      .collect()
+  }
+
+  #[test]
+  fn test_encode_segments_matches_per_segment_encoding() {
+    let config = ErasureConfig::tiny();
+    let segments = segments(3);
+    let refs: Vec<&[u8]> = segments.iter().map(|s| &s[..]).collect();
+
+    let encoded = encode_segments(&config, &refs).unwrap();
+    let chunk_len = segment_chunk_len(&config);
+
+    for (j, segment) in segments.iter().enumerate() {
+      let chunks = codec::encode(&config, segment).unwrap();
+      for (validator, chunk) in chunks.iter().enumerate() {
+        let offset = j * chunk_len;
+        assert_eq!(&encoded[validator][offset..offset + chunk_len], &chunk[..]);
+      }
+    }
+  }
+
+  #[test]
+  fn test_reconstruct_segments_by_index() {
+    for config in [ErasureConfig::tiny(), ErasureConfig::full()] {
+      let segments = segments(3);
+      let refs: Vec<&[u8]> = segments.iter().map(|s| &s[..]).collect();
+      let encoded = encode_segments(&config, &refs).unwrap();
+
+      let available: Vec<(u16, &[u8])> = encoded
+        .iter()
+        .enumerate()
+        .rev()
+        .take(config.recovery_threshold())
+        .map(|(i, chunks)| (i as u16, &chunks[..]))
+        .collect();
+
+      let rebuilt =
+        reconstruct_segments(&config, &available, 3, &[2, 0]).unwrap();
+      assert_eq!(rebuilt, vec![segments[2].clone(), segments[0].clone()]);
+    }
+  }
+
+  #[test]
+  fn test_reconstruct_segments_rejects_bad_index() {
+    let config = ErasureConfig::tiny();
+    let segments = segments(1);
+    let encoded = encode_segments(&config, &[&segments[0]]).unwrap();
+    let available = [(0u16, &encoded[0][..]), (1, &encoded[1][..])];
+
+    assert!(matches!(
+      reconstruct_segments(&config, &available, 1, &[1]),
+      Err(Error::SegmentIndexOutOfRange(1))
+    ));
+  }
+}
@@ -0,0 +1,364 @@
+//! On-disk availability store for erasure-coded chunks
+//!
+//! Chunks are kept in plain files under a caller-given directory, one
+//! subdirectory per erasure root:
+//!
+//! ```text
+//! <dir>/<hex erasure root>/timeslot   -- u32 LE, latest timeslot stored
+//! <dir>/<hex erasure root>/<index>    -- chunk bytes of validator <index>
+//! ```
+//!
+//! Each file is written to a uniquely named temporary file in the same
+//! directory, synced, renamed into place and the directory synced after, so
+//! neither a crash nor concurrent writers of the same chunk leave a partially
+//! written or missing chunk behind.
+
+use std::{
+  fs,
+  io::{self, Write},
+  path::{Path, PathBuf},
+};
+
+use thiserror::Error;
+
+use crate::{codec, config::ErasureConfig, merkle::Hash};
+
+const TIMESLOT_FILE: &str = "timeslot";
+
+#[derive(Error, Debug)]
+pub enum StoreError {
+  #[error(transparent)]
+  Io(#[from] io::Error),
+  #[error(transparent)]
+  Codec(#[from] codec::Error),
+  #[error("Corrupt timeslot file for erasure root {0}")]
+  CorruptTimeslot(String),
+}
+
+/// Availability store rooted at a directory.
+pub struct ChunkStore {
+  dir: PathBuf,
+}
+
+/// Flushes the entries of `dir`, making renames and creations in it durable.
+fn sync_dir(dir: &Path) -> io::Result<()> {
+  #[cfg(unix)]
+  fs::File::open(dir)?.sync_all()?;
+  #[cfg(not(unix))]
+  let _ = dir;
+  Ok(())
+}
+
+fn write_atomic(path: &Path, contents: &[u8]) -> io::Result<()> {
+  let dir = path.parent().expect("store files live in a directory");
+  let mut temp = tempfile::NamedTempFile::new_in(dir)?;
+  temp.write_all(contents)?;
+  temp.as_file().sync_all()?;
+  temp.persist(path).map_err(|e| e.error)?;
+  sync_dir(dir)
+}
+
+/// Decodes a root directory name back into its erasure root.
+fn parse_root_name(name: &str) -> Option<Hash> {
+  let mut root = [0u8; 32];
+  hex::decode_to_slice(name, &mut root).ok()?;
+  Some(root)
+}
+
+fn read_optional(path: &Path) -> io::Result<Option<Vec<u8>>> {
+  match fs::read(path) {
+    Ok(bytes) => Ok(Some(bytes)),
+    Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(None),
+    Err(e) => Err(e),
+  }
+}
+
+impl ChunkStore {
+  /// Opens the store in `dir`, creating the directory if needed.
+  pub fn open(dir: impl Into<PathBuf>) -> io::Result<Self> {
+    let dir = dir.into();
+    fs::create_dir_all(&dir)?;
+    Ok(Self { dir })
+  }
+
+  fn root_dir(&self, erasure_root: &Hash) -> PathBuf {
+    self.dir.join(hex::encode(erasure_root))
+  }
+
+  fn read_timeslot(root_dir: &Path) -> Result<Option<u32>, StoreError> {
+    match read_optional(&root_dir.join(TIMESLOT_FILE))? {
+      None => Ok(None),
+      Some(bytes) => {
+        let bytes: [u8; 4] = bytes.try_into().map_err(|_| {
+          StoreError::CorruptTimeslot(root_dir.display().to_string())
+        })?;
+        Ok(Some(u32::from_le_bytes(bytes)))
+      }
+    }
+  }
+
+  /// Stores the chunk of validator `index` for `erasure_root`.
+  ///
+  /// `timeslot` is the slot the chunk belongs to. The root expires once
+  /// `expire` is called with a later slot than the latest one stored.
+  pub fn put(
+    &self,
+    erasure_root: &Hash,
+    index: u16,
+    chunk: &[u8],
+    timeslot: u32,
+  ) -> Result<(), StoreError> {
+    let root_dir = self.root_dir(erasure_root);
+    if !root_dir.is_dir() {
+      fs::create_dir_all(&root_dir)?;
+      sync_dir(&self.dir)?;
+    }
+
+    if Self::read_timeslot(&root_dir)?.is_none_or(|stored| stored < timeslot) {
+      write_atomic(&root_dir.join(TIMESLOT_FILE), &timeslot.to_le_bytes())?;
+    }
+    write_atomic(&root_dir.join(index.to_string()), chunk)?;
+
+    Ok(())
+  }
+
+  /// Returns the chunk of validator `index` for `erasure_root`, if stored.
+  pub fn get(
+    &self,
+    erasure_root: &Hash,
+    index: u16,
+  ) -> Result<Option<Vec<u8>>, StoreError> {
+    let path = self.root_dir(erasure_root).join(index.to_string());
+    Ok(read_optional(&path)?)
+  }
+
+  /// Returns the indices of all chunks stored for `erasure_root`, sorted.
+  pub fn indices(&self, erasure_root: &Hash) -> Result<Vec<u16>, StoreError> {
+    let entries = match fs::read_dir(self.root_dir(erasure_root)) {
+      Ok(entries) => entries,
+      Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+      Err(e) => return Err(e.into()),
+    };
+
+    let mut indices = Vec::new();
+    for entry in entries {
+      if let Some(index) = entry?
+        .file_name()
+        .to_str()
+        .and_then(|name| name.parse::<u16>().ok())
+      {
+        indices.push(index);
+      }
+    }
+    indices.sort_unstable();
+
+    Ok(indices)
+  }
+
+  /// Removes every erasure root whose latest timeslot is before
+  /// `before_timeslot`, returning how many were removed.
+  ///
+  /// Only directories named after an erasure root are considered. A root
+  /// without a timeslot file is kept, as a concurrent `put` may have just
+  /// created it.
+  pub fn expire(&self, before_timeslot: u32) -> Result<usize, StoreError> {
+    let mut removed = 0;
+
+    for entry in fs::read_dir(&self.dir)? {
+      let entry = entry?;
+      let is_root = entry
+        .file_name()
+        .to_str()
+        .and_then(parse_root_name)
+        .is_some();
+      let root_dir = entry.path();
+      if !is_root || !root_dir.is_dir() {
+        continue;
+      }
+
+      let expired = Self::read_timeslot(&root_dir)?
+        .is_some_and(|timeslot| timeslot < before_timeslot);
+      if expired {
+        fs::remove_dir_all(&root_dir)?;
+        removed += 1;
+      }
+    }
+
+    Ok(removed)
+  }
+
+  /// Reconstructs the `data_len` bytes committed to by `erasure_root` from
+  /// locally stored chunks.
+  ///
+  /// Returns `None` if fewer chunks than the recovery threshold are stored.
+  /// Chunks that vanish while reading, or whose length does not match
+  /// `data_len`, are passed over in favour of other stored chunks.
+  /// Systematic chunks are preferred so the decode can be skipped when they
+  /// are all present.
+  pub fn reconstruct(
+    &self,
+    config: &ErasureConfig,
+    erasure_root: &Hash,
+    data_len: usize,
+  ) -> Result<Option<Vec<u8>>, StoreError> {
+    let needed = config.recovery_threshold();
+    let chunk_len = config.chunk_len(data_len);
+
+    // Indices are sorted, so the systematic ones come first
+    let mut chunks = Vec::with_capacity(needed);
+    for index in self.indices(erasure_root)? {
+      if chunks.len() == needed || index >= config.chunk_count() {
+        break;
+      }
+      match self.get(erasure_root, index)? {
+        Some(chunk) if chunk.len() == chunk_len => chunks.push((index, chunk)),
+        _ => {}
+      }
+    }
+    if chunks.len() < needed {
+      return Ok(None);
+    }
+
+    let data = codec::reconstruct(
+      config,
+      chunks
+        .iter()
+        .map(|(index, chunk)| (*index, chunk.as_slice())),
+      data_len,
+    )?;
+
+    Ok(Some(data))
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn root(byte: u8) -> Hash {
+    [byte; 32]
+  }
+
+  #[test]
+  fn test_put_and_get() {
+    let dir = tempfile::tempdir().unwrap();
+    let store = ChunkStore::open(dir.path()).unwrap();
+
+    store.put(&root(1), 3, b"chunk three", 10).unwrap();
+
+    assert_eq!(store.get(&root(1), 3).unwrap().unwrap(), b"chunk three");
+    assert!(store.get(&root(1), 4).unwrap().is_none());
+    assert!(store.get(&root(2), 3).unwrap().is_none());
+    assert_eq!(store.indices(&root(1)).unwrap(), vec![3]);
+  }
+
+  #[test]
+  fn test_survives_reopen() {
+    let dir = tempfile::tempdir().unwrap();
+    ChunkStore::open(dir.path())
+      .unwrap()
+      .put(&root(1), 0, b"durable", 5)
+      .unwrap();
+
+    let store = ChunkStore::open(dir.path()).unwrap();
+    assert_eq!(store.get(&root(1), 0).unwrap().unwrap(), b"durable");
+  }
+
+  #[test]
+  fn test_concurrent_puts_never_tear_a_chunk() {
+    let dir = tempfile::tempdir().unwrap();
+    let store = ChunkStore::open(dir.path()).unwrap();
+    let chunks: Vec<Vec<u8>> = (1..=4u8).map(|b| vec![b; 4096]).collect();
+
+    std::thread::scope(|scope| {
+      for chunk in &chunks {
+        let store = &store;
+        scope.spawn(move || {
+          for timeslot in 0..50 {
+            store.put(&root(1), 7, chunk, timeslot).unwrap();
+          }
+        });
+      }
+    });
+
+    let stored = store.get(&root(1), 7).unwrap().unwrap();
+    assert!(chunks.contains(&stored));
+    // Only the chunk and the timeslot file remain, no temporary files
+    let names = fs::read_dir(store.root_dir(&root(1))).unwrap().count();
+    assert_eq!(names, 2);
+  }
+
+  #[test]
+  fn test_expire_by_timeslot() {
+    let dir = tempfile::tempdir().unwrap();
+    let store = ChunkStore::open(dir.path()).unwrap();
+
+    store.put(&root(1), 0, b"old", 10).unwrap();
+    store.put(&root(2), 0, b"new", 20).unwrap();
+    // A later chunk keeps the whole root alive
+    store.put(&root(1), 1, b"refreshed", 15).unwrap();
+
+    assert_eq!(store.expire(15).unwrap(), 0);
+    assert_eq!(store.expire(16).unwrap(), 1);
+    assert!(store.get(&root(1), 0).unwrap().is_none());
+    assert!(store.get(&root(2), 0).unwrap().is_some());
+  }
+
+  #[test]
+  fn test_expire_leaves_other_directories_alone() {
+    let dir = tempfile::tempdir().unwrap();
+    let store = ChunkStore::open(dir.path()).unwrap();
+
+    store.put(&root(1), 0, b"old", 10).unwrap();
+    fs::create_dir(dir.path().join("unrelated")).unwrap();
+    // A root whose first put has not written its timeslot yet
+    fs::create_dir(dir.path().join(hex::encode(root(2)))).unwrap();
+
+    assert_eq!(store.expire(20).unwrap(), 1);
+    assert!(dir.path().join("unrelated").is_dir());
+    assert!(dir.path().join(hex::encode(root(2))).is_dir());
+  }
+
+  #[test]
+  fn test_reconstruct_passes_over_bad_chunks() {
+    let dir = tempfile::tempdir().unwrap();
+    let store = ChunkStore::open(dir.path()).unwrap();
+    let config = ErasureConfig::tiny();
+    let data: Vec<u8> = (0..200u8).collect();
+    let chunks = codec::encode(&config, &data).unwrap();
+
+    store.put(&root(7), 0, &chunks[0][1..], 1).unwrap();
+    store.put(&root(7), 3, &chunks[3], 1).unwrap();
+    assert!(store
+      .reconstruct(&config, &root(7), data.len())
+      .unwrap()
+      .is_none());
+
+    store.put(&root(7), 5, &chunks[5], 1).unwrap();
+    assert_eq!(
+      store.reconstruct(&config, &root(7), data.len()).unwrap(),
+      Some(data)
+    );
+  }
+
+  #[test]
+  fn test_reconstruct_when_enough_chunks_are_local() {
+    let dir = tempfile::tempdir().unwrap();
+    let store = ChunkStore::open(dir.path()).unwrap();
+    let config = ErasureConfig::tiny();
+    let data: Vec<u8> = (0..200u8).collect();
+    let chunks = codec::encode(&config, &data).unwrap();
+
+    store.put(&root(7), 4, &chunks[4], 1).unwrap();
+    assert!(store
+      .reconstruct(&config, &root(7), data.len())
+      .unwrap()
+      .is_none());
+
+    store.put(&root(7), 2, &chunks[2], 1).unwrap();
+    assert_eq!(
+      store.reconstruct(&config, &root(7), data.len()).unwrap(),
+      Some(data)
+    );
+  }
+}
@@ -0,0 +1,171 @@
+//! Runs the jamtestvectors erasure coding vectors against this crate
+//!
+//! The vectors live in the `src/jamtestvectors/data` submodule of the
+//! repository. Each file holds the original `data` and the expected
+//! `shards`, one per validator, for the tiny or full chain spec. The tests
+//! are ignored by default since the submodule is optional; check it out with
+//! `git submodule update --init` and run them with `cargo test -- --ignored`.
+//! A missing submodule then fails the tests.
+//!
+//! The vectors publish no erasure root, so the root of the encoded data is
+//! checked against M_B computed directly from the published shards, written
+//! out here as the plain recursion of appendix E rather than through
+//! `merkle`.
+
+use std::{
+  fs,
+  path::{Path, PathBuf},
+};
+
+use rand::{rngs::StdRng, seq::SliceRandom, SeedableRng};
+
+use crate::{codec, config::ErasureConfig, erasure_root, merkle};
+
+const VECTORS_PATH: &str = "../../../src/jamtestvectors/data/erasure";
+
+struct Vector {
+  name: String,
+  data: Vec<u8>,
+  shards: Vec<Vec<u8>>,
+}
+
+fn decode_hex(value: &serde_json::Value) -> Vec<u8> {
+  let hex_str = value.as_str().expect("hex string");
+  hex::decode(hex_str.trim_start_matches("0x")).expect("valid hex")
+}
+
+/// Loads the vectors of `spec`, panicking if the submodule is not checked
+/// out.
+fn load_vectors(spec: &str) -> Vec<Vector> {
+  let dir = Path::new(env!("CARGO_MANIFEST_DIR"))
+    .join(VECTORS_PATH)
+    .join(spec);
+  let entries = fs::read_dir(&dir).unwrap_or_else(|e| {
+    panic!(
+      "{}: {e}; run `git submodule update --init` to fetch the vectors",
+      dir.display()
+    )
+  });
+
+  let mut paths: Vec<PathBuf> = entries
+    .map(|entry| entry.expect("directory entry").path())
+    .filter(|path| path.extension().is_some_and(|ext| ext == "json"))
+    .collect();
+  paths.sort();
+  assert!(!paths.is_empty(), "No vectors found in {}", dir.display());
+
+  paths
+    .into_iter()
+    .map(|path| {
+      let json: serde_json::Value =
+        serde_json::from_slice(&fs::read(&path).expect("readable vector"))
+          .expect("valid json");
+
+      Vector {
+        name: path.file_name().unwrap().to_string_lossy().into_owned(),
+        data: decode_hex(&json["data"]),
+        shards: json["shards"]
+          .as_array()
+          .expect("shards array")
+          .iter()
+          .map(decode_hex)
+          .collect(),
+      }
+    })
+    .collect()
+}
+
+/// M_B of appendix E: `H("node" ++ N(left) ++ N(right))` over the halves,
+/// with a single-leaf subtree being the leaf itself and the root of a
+/// single-leaf tree being its hash.
+fn reference_root(leaves: &[Vec<u8>]) -> merkle::Hash {
+  fn node(leaves: &[Vec<u8>]) -> Vec<u8> {
+    match leaves.len() {
+      0 => vec![0u8; merkle::HASH_LENGTH],
+      1 => leaves[0].clone(),
+      n => {
+        let mid = n.div_ceil(2);
+        let preimage =
+          [&b"node"[..], &node(&leaves[..mid]), &node(&leaves[mid..])].concat();
+        merkle::hash(&preimage).to_vec()
+      }
+    }
+  }
+
+  match leaves.len() {
+    1 => merkle::hash(&leaves[0]),
+    _ => node(leaves).try_into().unwrap(),
+  }
+}
+
+fn run_vectors(config: &ErasureConfig, spec: &str) {
+  let mut rng = StdRng::seed_from_u64(0x6a616d);
+  let n_chunks = config.chunk_count() as usize;
+
+  for vector in load_vectors(spec) {
+    let name = &vector.name;
+    assert_eq!(vector.shards.len(), n_chunks, "{name}: shard count");
+
+    // Encoding must reproduce the published shards exactly
+    let chunks = codec::encode(config, &vector.data).unwrap();
+    assert_eq!(chunks, vector.shards, "{name}: encoded shards");
+
+    // Any recovery-threshold sized subset must give back the data
+    let mut indices: Vec<usize> = (0..n_chunks).collect();
+    for _ in 0..4 {
+      indices.shuffle(&mut rng);
+      let subset = indices[..config.recovery_threshold()]
+        .iter()
+        .map(|&i| (i as u16, vector.shards[i].as_slice()));
+      let data = codec::reconstruct(config, subset, vector.data.len())
+        .unwrap_or_else(|e| panic!("{name}: reconstruct failed: {e}"));
+      assert_eq!(data, vector.data, "{name}: reconstructed data");
+    }
+
+    let systematic = config
+      .systematic_indices()
+      .map(|i| (i, vector.shards[i as usize].as_slice()));
+    let data =
+      codec::reconstruct(config, systematic, vector.data.len()).unwrap();
+    assert_eq!(data, vector.data, "{name}: systematic reconstruction");
+
+    // The erasure root of the encoded data must be M_B over the hashes of
+    // the published shards, and every published shard must verify against
+    // it under its justification
+    let encoded =
+      erasure_root::encode_with_root(config, &vector.data, &[]).unwrap();
+    let leaves: Vec<Vec<u8>> = vector
+      .shards
+      .iter()
+      .map(|shard| merkle::hash(shard).to_vec())
+      .collect();
+    let root = reference_root(&leaves);
+    assert_eq!(encoded.erasure_root, root, "{name}: erasure root");
+
+    for (i, shard) in vector.shards.iter().enumerate() {
+      assert!(
+        erasure_root::verify_chunk(
+          config,
+          i as u16,
+          shard,
+          &[],
+          &encoded.justifications[i],
+          &root,
+        ),
+        "{name}: justification of chunk {i}"
+      );
+    }
+  }
+}
+
+#[test]
+#[ignore = "needs the jamtestvectors submodule"]
+fn test_tiny_vectors() {
+  run_vectors(&ErasureConfig::tiny(), "tiny");
+}
+
+#[test]
+#[ignore = "needs the jamtestvectors submodule"]
+fn test_full_vectors() {
+  run_vectors(&ErasureConfig::full(), "full");
+}